@@ -0,0 +1,152 @@
+// Lookups in the global registry, against the lookup it replaced: `get_vtable` of the baseline,
+// on a map of implementors to maps of traits, both hashed with SipHash, so two hashed lookups
+// per cast, then the same errors built and turned into an `implements` answer.
+use std::any::{type_name, TypeId};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::LazyLock;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use iza_trait_cast::register_types;
+use iza_trait_cast::trait_registry::{self, Castable, CastError};
+
+trait Base: Castable {}
+trait Child: Base {}
+trait Unregistered: Base {}
+
+struct Node<const N: usize>;
+impl<const N: usize> Base for Node<N> {}
+impl<const N: usize> Child for Node<N> {}
+impl<const N: usize> Unregistered for Node<N> {}
+
+struct Leaf<const N: usize>;
+impl<const N: usize> Base for Leaf<N> {}
+
+register_types!{
+    implementors: [
+        Node<N> for const N: usize in (0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
+        Leaf<N> for const N: usize in (0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15)
+    ],
+    traits: [Base, Child, dyn Base + Send, dyn Child + Send]
+}
+
+// More types than the `inline-cache` slots of a trait, for lookups that keep evicting each other
+struct Wide<const A: usize, const B: usize>;
+impl<const A: usize, const B: usize> Base for Wide<A, B> {}
+impl<const A: usize, const B: usize> Child for Wide<A, B> {}
+
+register_types!{
+    implementors: [
+        Wide<A, B> for const A: usize in (0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15)
+            for const B: usize in (0, 1, 2, 3, 4, 5, 6, 7)
+    ],
+    traits: [Base, Child]
+}
+
+fn wide_row<const A: usize>() -> [&'static dyn Castable; 8] {
+    [&Wide::<A, 0>, &Wide::<A, 1>, &Wide::<A, 2>, &Wide::<A, 3>, &Wide::<A, 4>, &Wide::<A, 5>, &Wide::<A, 6>, &Wide::<A, 7>]
+}
+
+/// Every (object, lookup) pair of the `Wide` types, by lookup: 128 types, twice as many as the
+/// cache has slots for each of the 4 trait object types
+fn wide_pairs() -> Vec<(&'static dyn Castable, Implements)> {
+    let rows = [
+        wide_row::<0>(), wide_row::<1>(), wide_row::<2>(), wide_row::<3>(), wide_row::<4>(), wide_row::<5>(), wide_row::<6>(), wide_row::<7>(),
+        wide_row::<8>(), wide_row::<9>(), wide_row::<10>(), wide_row::<11>(), wide_row::<12>(), wide_row::<13>(), wide_row::<14>(), wide_row::<15>(),
+    ];
+    let lookups: [Implements; 4] = [
+        trait_registry::implements::<dyn Base>,
+        trait_registry::implements::<dyn Child>,
+        trait_registry::implements::<dyn Base + Send>,
+        trait_registry::implements::<dyn Child + Send + Sync>,
+    ];
+    lookups.into_iter().flat_map(|implements| rows.into_iter().flatten().map(move |obj| (obj, implements))).collect()
+}
+
+// The baseline's vtable: only copied around, never used, so any pointer does
+#[derive(Clone, Copy)]
+struct BaselineVTable(#[allow(dead_code)] &'static ());
+
+#[allow(dead_code)]
+enum BaselineCastError {
+    TraitNotImplemented { trait_name: &'static str, trait_id: TypeId, type_name: &'static str, type_id: TypeId },
+    CombinationNotRegistered { trait_name: &'static str, trait_id: TypeId, type_name: &'static str, type_id: TypeId },
+}
+
+/// The baseline's registry, filled with the same registrations
+static BASELINE_REGISTRY: LazyLock<HashMap<TypeId, HashMap<TypeId, Option<BaselineVTable>>>> = LazyLock::new(|| {
+    trait_registry::implementors_of::<dyn Base>().iter().map(|implementor| {
+        let registered = trait_registry::registered_traits_of_type_id(implementor.type_id).unwrap();
+        let implemented = registered.implemented.iter().map(|info| (info.trait_id, Some(BaselineVTable(&()))));
+        let not_implemented = registered.not_implemented.iter().map(|info| (info.trait_id, None));
+        (implementor.type_id, implemented.chain(not_implemented).collect())
+    }).collect()
+});
+
+/// `get_vtable` of the baseline
+fn baseline_get_vtable<TCastTo: ?Sized + 'static>(obj: &dyn Castable) -> Result<BaselineVTable, BaselineCastError> {
+    let obj_type_id = obj.type_id();
+    match BASELINE_REGISTRY.get(&obj_type_id) {
+        Some(type_registration) => match type_registration.get(&TypeId::of::<TCastTo>()) {
+            None => Err(BaselineCastError::CombinationNotRegistered { trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj.type_name(), type_id: obj_type_id }),
+            Some(None) => Err(BaselineCastError::TraitNotImplemented { trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj.type_name(), type_id: obj_type_id }),
+            Some(Some(found)) => Ok(*found),
+        },
+        None => Err(BaselineCastError::CombinationNotRegistered { trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj.type_name(), type_id: obj_type_id }),
+    }
+}
+
+/// The answer `implements` gives, out of the baseline's lookup
+fn baseline_implements<TCastTo: ?Sized + 'static>(obj: &dyn Castable) -> Result<bool, BaselineCastError> {
+    match baseline_get_vtable::<TCastTo>(obj) {
+        Ok(_) => Ok(true),
+        Err(BaselineCastError::TraitNotImplemented { .. }) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+type Implements = fn(&dyn Castable) -> Result<bool, CastError>;
+type BaselineImplements = fn(&dyn Castable) -> Result<bool, BaselineCastError>;
+
+fn lookup(c: &mut Criterion) {
+    // So a broken registration does not pass for a fast lookup
+    assert!(matches!(trait_registry::implements::<dyn Child>(&Node::<7>), Ok(true)));
+    assert!(matches!(trait_registry::implements::<dyn Child>(&Leaf::<7>), Ok(false)));
+    assert!(matches!(trait_registry::implements::<dyn Unregistered>(&Node::<7>), Err(CastError::CombinationNotRegistered { .. })));
+
+    assert!(matches!(baseline_implements::<dyn Child>(&Node::<7>), Ok(true)));
+    assert!(matches!(baseline_implements::<dyn Child>(&Leaf::<7>), Ok(false)));
+    assert!(matches!(baseline_implements::<dyn Unregistered>(&Node::<7>), Err(BaselineCastError::CombinationNotRegistered { .. })));
+    let cases: [(&str, &dyn Castable, BaselineImplements, Implements); 3] = [
+        ("hit", &Node::<7>, baseline_implements::<dyn Child>, trait_registry::implements::<dyn Child>),
+        ("miss", &Leaf::<7>, baseline_implements::<dyn Child>, trait_registry::implements::<dyn Child>),
+        ("not_registered", &Node::<7>, baseline_implements::<dyn Unregistered>, trait_registry::implements::<dyn Unregistered>),
+    ];
+    for (case, obj, baseline_implements, implements) in cases {
+        let mut group = c.benchmark_group(case);
+        group.bench_function("nested_siphash", |b| b.iter(|| baseline_implements(black_box(obj)).ok()));
+        group.bench_function("flat_identity", |b| b.iter(|| implements(black_box(obj)).ok()));
+        group.finish();
+    }
+}
+
+// Cycles through working sets of (object, lookup) pairs. With `inline-cache` each trait object type
+// has its own slots, so once the types looked up for one of them outgrow those, they evict each
+// other and lookups fall back to the registry: compare against a build without the feature
+fn working_set(c: &mut Criterion) {
+    let pairs = wide_pairs();
+    assert!(pairs.iter().all(|(obj, implements)| matches!(implements(*obj), Ok(true))));
+    let mut group = c.benchmark_group("working_set");
+    for size in [16, 128, 512] {
+        let working_set = &pairs[..size];
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), working_set, |b, working_set| b.iter(|| {
+            for (obj, implements) in working_set {
+                black_box(implements(black_box(*obj)).ok());
+            }
+        }));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lookup, working_set);
+criterion_main!(benches);
@@ -0,0 +1,215 @@
+//! Attribute macros for `iza_trait_cast`. They expand to `register_types!` invocations, so use
+//! them through the re-exports in `iza_trait_cast`.
+
+use std::collections::HashMap;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    parse_macro_input, AngleBracketedGenericArguments, Data, DeriveInput, Expr, GenericArgument, GenericParam, Ident,
+    ItemImpl, Path, Token, Type,
+};
+
+/// Registers the annotated struct or enum for every listed trait.
+///
+/// ```ignore
+/// #[castable(Base, Child)]
+/// struct Foo;
+/// ```
+///
+/// expands to the type definition, a compile time check that `Foo` implements `Base` and
+/// `Child`, and `register_types!{ implementors: [Foo], traits: [Base, Child] }`.
+#[proc_macro_attribute]
+pub fn castable(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let traits = match Punctuated::<Path, Token![,]>::parse_terminated.parse(attr) {
+        Ok(traits) => traits,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let input = parse_macro_input!(item as DeriveInput);
+    match expand_castable(&input, &traits) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => {
+            let err = err.to_compile_error();
+            quote!(#input #err).into()
+        }
+    }
+}
+
+fn expand_castable(input: &DeriveInput, traits: &Punctuated<Path, Token![,]>) -> syn::Result<TokenStream2> {
+    if let Data::Union(data) = &input.data {
+        return Err(syn::Error::new(data.union_token.span(), "#[castable] can only be used on structs and enums"));
+    }
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "#[castable] can not register a generic type, register its concrete instantiations with register_types!",
+        ));
+    }
+    let ident = &input.ident;
+    let assertions = traits.iter().map(|trait_path| assert_implements(&quote!(#ident), trait_path));
+    let traits = traits.iter();
+    Ok(quote! {
+        #input
+        #(#assertions)*
+        ::iza_trait_cast::register_types!{
+            implementors: [#ident],
+            traits: [#(#traits),*]
+        }
+    })
+}
+
+/// Fails to compile, pointing at `trait_path`, unless `implementor` implements it
+fn assert_implements(implementor: &TokenStream2, trait_path: &Path) -> TokenStream2 {
+    quote_spanned! {trait_path.span()=>
+        const _: fn() = || {
+            fn assert_implements<T: ?::core::marker::Sized + #trait_path>() {}
+            assert_implements::<#implementor>();
+        };
+    }
+}
+
+/// Registers exactly the (type, trait) pair of the annotated trait impl.
+///
+/// ```ignore
+/// #[register_impl]
+/// impl Child for Foo { /* ... */ }
+/// ```
+///
+/// Generic impls need the concrete instantiations to register, one `<...>` list of generic
+/// arguments per instantiation, in the order the impl declares its parameters:
+///
+/// ```ignore
+/// #[register_impl(<u8, 16>, <String, 32>)]
+/// impl<T, const N: usize> Handler<T> for Buffer<T, N> { /* ... */ }
+/// ```
+#[proc_macro_attribute]
+pub fn register_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let instantiations = match Punctuated::<AngleBracketedGenericArguments, Token![,]>::parse_terminated.parse(attr) {
+        Ok(instantiations) => instantiations,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let input = parse_macro_input!(item as ItemImpl);
+    match expand_register_impl(&input, &instantiations) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => {
+            let err = err.to_compile_error();
+            quote!(#input #err).into()
+        }
+    }
+}
+
+fn expand_register_impl(input: &ItemImpl, instantiations: &Punctuated<AngleBracketedGenericArguments, Token![,]>) -> syn::Result<TokenStream2> {
+    let Some((negative, trait_path, _)) = &input.trait_ else {
+        return Err(syn::Error::new(input.self_ty.span(), "#[register_impl] must be placed on a trait impl"));
+    };
+    if let Some(negative) = negative {
+        return Err(syn::Error::new(negative.span(), "#[register_impl] can not register a negative impl"));
+    }
+    let params: Vec<&GenericParam> = input.generics.params.iter().collect();
+    if let Some(lifetime) = input.generics.lifetimes().next() {
+        return Err(syn::Error::new(lifetime.span(), "#[register_impl] can not register impls with lifetime parameters"));
+    }
+
+    let mut registrations = Vec::new();
+    if params.is_empty() {
+        if let Some(instantiation) = instantiations.first() {
+            return Err(syn::Error::new(instantiation.span(), "instantiations can only be listed for generic impls"));
+        }
+        registrations.push(register_pair(&input.self_ty, trait_path));
+    } else {
+        if instantiations.is_empty() {
+            return Err(syn::Error::new(
+                input.generics.span(),
+                "generic impls need the concrete instantiations to register, e.g. #[register_impl(<u8>, <String>)]",
+            ));
+        }
+        for instantiation in instantiations {
+            if instantiation.args.len() != params.len() {
+                return Err(syn::Error::new(
+                    instantiation.span(),
+                    format!("expected {} generic arguments, one for each parameter of the impl", params.len()),
+                ));
+            }
+            let mut substitute = Substitute { args: HashMap::new() };
+            for (param, arg) in params.iter().zip(&instantiation.args) {
+                let ident = match param {
+                    GenericParam::Type(param) => param.ident.clone(),
+                    GenericParam::Const(param) => param.ident.clone(),
+                    GenericParam::Lifetime(_) => unreachable!("rejected above"),
+                };
+                substitute.args.insert(ident, arg.clone());
+            }
+            let mut self_ty = (*input.self_ty).clone();
+            substitute.visit_type_mut(&mut self_ty);
+            let mut trait_path = trait_path.clone();
+            substitute.visit_path_mut(&mut trait_path);
+            registrations.push(register_pair(&self_ty, &trait_path));
+        }
+    }
+    Ok(quote! {
+        #input
+        #(#registrations)*
+    })
+}
+
+fn register_pair(implementor: &Type, trait_path: &Path) -> TokenStream2 {
+    let assertion = assert_implements(&implementor.to_token_stream(), trait_path);
+    quote! {
+        #assertion
+        ::iza_trait_cast::register_types!{
+            implementors: [#implementor],
+            traits: [#trait_path]
+        }
+    }
+}
+
+/// Replaces the impl's generic parameters with the arguments of one instantiation
+struct Substitute {
+    args: HashMap<Ident, GenericArgument>,
+}
+
+impl Substitute {
+    fn lookup(&self, path: &Path) -> Option<&GenericArgument> {
+        path.get_ident().and_then(|ident| self.args.get(ident))
+    }
+}
+
+impl VisitMut for Substitute {
+    fn visit_generic_argument_mut(&mut self, arg: &mut GenericArgument) {
+        if let GenericArgument::Type(Type::Path(type_path)) = arg
+            && type_path.qself.is_none()
+            && let Some(replacement) = self.lookup(&type_path.path)
+        {
+            *arg = replacement.clone();
+            return;
+        }
+        visit_mut::visit_generic_argument_mut(self, arg);
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(type_path) = ty
+            && type_path.qself.is_none()
+            && let Some(GenericArgument::Type(replacement)) = self.lookup(&type_path.path)
+        {
+            *ty = replacement.clone();
+            return;
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Path(expr_path) = expr
+            && expr_path.qself.is_none()
+            && let Some(GenericArgument::Const(replacement)) = self.lookup(&expr_path.path)
+        {
+            *expr = replacement.clone();
+            return;
+        }
+        visit_mut::visit_expr_mut(self, expr);
+    }
+}
@@ -0,0 +1,35 @@
+use core::any::type_name;
+use core::error::Error;
+use core::fmt::{Debug, Display, Formatter};
+use crate::trait_registry::CastError;
+
+pub struct CastErrorWith<T>{
+    pub error: CastError,
+    pub with: T
+}
+
+impl<T> CastErrorWith<T> {
+    pub(crate) fn new(error: CastError, with: T) -> Self {
+        Self { error, with }
+    }
+    pub(crate) fn map_with<U>(self, f: impl FnOnce(T) -> U) -> CastErrorWith<U> {
+        CastErrorWith::new(self.error, f(self.with))
+    }
+}
+impl<T> Debug for CastErrorWith<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.error, f)
+    }
+}
+// Only names what was being cast: why it failed is the source, so error reports that walk the
+// chain do not print it twice
+impl<T> Display for CastErrorWith<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_fmt(format_args!("could not cast '{}'", type_name::<T>()))
+    }
+}
+impl<T> Error for CastErrorWith<T> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -1,23 +1,33 @@
-use std::alloc::Allocator;
-use std::fmt::{Debug, Formatter};
-use std::marker::Unsize;
-use std::ptr;
+use alloc::alloc::Allocator;
+use alloc::boxed::Box;
+use core::cell::{Ref, RefMut};
+use core::marker::Unsize;
+use core::pin::Pin;
+use core::ptr::{self, NonNull};
 use crate::handy_functions::generic_transmute;
-use crate::trait_registry::{get_vtable, Castable,CastError};
-use std::ptr::{DynMetadata, Pointee};
-use std::rc::Rc;
-use std::sync::Arc;
+pub use crate::cast_error_with::CastErrorWith;
+pub use crate::cross_cast::CrossCast;
+use crate::cross_cast::{cast_cell_ref, cast_cell_ref_mut, cast_mut_or_return, cast_pinned};
+#[cfg(feature = "std")]
+use crate::cross_cast::cast_guard;
+use crate::trait_registry::{get_vtable, AutoTraits, CastTarget, Castable, CastError, Registry};
+use core::ptr::{DynMetadata, Pointee};
+use alloc::rc::{self, Rc};
+use alloc::sync::{self, Arc};
+#[cfg(feature = "std")]
+use std::sync::{MappedMutexGuard, MappedRwLockReadGuard, MappedRwLockWriteGuard, MutexGuard, RwLockReadGuard, RwLockWriteGuard};
 
-// macro, to avoid repeating code
+// macro, to avoid repeating code. `$carried` are the auto traits of the source's trait object
+// type, `$reg` is the registry to look in, `None` for the global one
 macro_rules! cast_reference {
-    ($TTo:ty, $from:expr,  $reg:expr) => {
+    ($TTo:ty, $from:expr, $carried:expr, $reg:expr) => {
         {
             unsafe{
-                let vtable =  get_vtable::<$TTo>($from,$reg);
+                let vtable =  get_vtable::<$TTo>($from,$carried,$reg);
                 match vtable {
                     Ok(vtable) => {
-                        let gotten : *const $TTo = ptr::from_raw_parts(ptr::addr_of!($from),generic_transmute(vtable));
-                        Ok(&*gotten)
+                        let gotten : *const $TTo = ptr::from_raw_parts($from as *const dyn Castable as *const (),generic_transmute(vtable));
+                        Ok(gotten)
                     }
                     Err(err) => {
                         Err(err)
@@ -28,103 +38,286 @@ macro_rules! cast_reference {
         }
     };
 }
-pub struct CastErrorWith<T>{
-    pub error: CastError,
-    pub with: T
+#[inline]
+pub fn trait_cross_cast_rc<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Rc<From, A>) -> Result<Rc<TTo, A>, CastErrorWith<Rc<From, A>>> {
+    cast_rc_with::<TTo, From, A>(from, None)
 }
 
-impl<T> CastErrorWith<T> {
-    fn new(error: CastError, with: T) -> Self {
-        Self { error, with }
-    }
+/// [`trait_cross_cast_rc`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_rc_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Rc<From, A>, registry: &Registry) -> Result<Rc<TTo, A>, CastErrorWith<Rc<From, A>>> {
+    cast_rc_with::<TTo, From, A>(from, Some(registry))
 }
-impl<T> Debug for CastErrorWith<T> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        self.error.fmt(f)
+
+#[inline]
+fn cast_rc_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Rc<From, A>, registry: Option<&Registry>) -> Result<Rc<TTo, A>, CastErrorWith<Rc<From, A>>> {
+    unsafe {
+        let gotten: (*const From, A) = Rc::into_raw_with_allocator(from);
+        match cast_ref_with::<TTo>(&*gotten.0, AutoTraits::of::<From>(), registry) {
+            Ok(casted) => {
+                Ok(Rc::from_raw_in(casted, gotten.1))
+            }
+            Err(err) => {
+                Err(CastErrorWith::new(err, Rc::from_raw_in(gotten.0, gotten.1)))
+            }
+        }
     }
 }
+
+/// Casts a `std::rc::Weak` without upgrading it.
+///
+/// The concrete type is read from the vtable of the weak pointer, so this also works when the
+/// strong count is zero or when the weak was created with `Weak::new`.
+#[inline]
+pub fn trait_cross_cast_rc_weak<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: rc::Weak<From, A>) -> Result<rc::Weak<TTo, A>, CastErrorWith<rc::Weak<From, A>>> {
+    cast_rc_weak_with::<TTo, From, A>(from, None)
+}
+
+/// [`trait_cross_cast_rc_weak`], looking only in `registry`.
 #[inline]
-pub fn trait_cross_cast_rc<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Rc<From, A>) -> Result<Rc<TTo, A>, CastErrorWith<Rc<From,A>>> {
+pub fn trait_cross_cast_rc_weak_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: rc::Weak<From, A>, registry: &Registry) -> Result<rc::Weak<TTo, A>, CastErrorWith<rc::Weak<From, A>>> {
+    cast_rc_weak_with::<TTo, From, A>(from, Some(registry))
+}
+
+#[inline]
+fn cast_rc_weak_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: rc::Weak<From, A>, registry: Option<&Registry>) -> Result<rc::Weak<TTo, A>, CastErrorWith<rc::Weak<From, A>>> {
     unsafe {
-        let gotten_rc : (*const From, A) = Rc::into_raw_with_allocator(from);
-        let as_ref = &*gotten_rc.0;
-        let casted = trait_cross_cast_ref::<TTo>(as_ref);
-        match casted {
+        let gotten: (*const From, A) = rc::Weak::into_raw_with_allocator(from);
+        match cast_ptr_with::<TTo, From>(gotten.0, registry) {
             Ok(casted) => {
-                return Ok(Rc::from_raw_in(casted, gotten_rc.1))
+                Ok(rc::Weak::from_raw_in(casted, gotten.1))
             }
             Err(err) => {
-                Err( CastErrorWith {
-                    with: Rc::from_raw_in(gotten_rc.0, gotten_rc.1),
-                    error: err
-                })
+                Err(CastErrorWith::new(err, rc::Weak::from_raw_in(gotten.0, gotten.1)))
             }
         }
     }
 }
 
 #[inline]
-pub fn trait_cross_cast_box<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Box<From, A>) -> Result<Box<TTo,A>, CastErrorWith<Box<From,A>>> {
+pub fn trait_cross_cast_box<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Box<From, A>) -> Result<Box<TTo, A>, CastErrorWith<Box<From, A>>> {
+    cast_box_with::<TTo, From, A>(from, None)
+}
+
+/// [`trait_cross_cast_box`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_box_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Box<From, A>, registry: &Registry) -> Result<Box<TTo, A>, CastErrorWith<Box<From, A>>> {
+    cast_box_with::<TTo, From, A>(from, Some(registry))
+}
+
+#[inline]
+fn cast_box_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Box<From, A>, registry: Option<&Registry>) -> Result<Box<TTo, A>, CastErrorWith<Box<From, A>>> {
     unsafe {
-        let gotten_rc: (*mut From, A) = Box::into_raw_with_allocator(from);
-        let as_ref = &mut *gotten_rc.0;
-        let casted = crate::cast_fns::trait_cross_cast_mut::<TTo>(as_ref);
-        match casted {
+        let gotten: (*mut From, A) = Box::into_raw_with_allocator(from);
+        match cast_mut_with::<TTo>(&mut *gotten.0, AutoTraits::of::<From>(), registry) {
             Ok(casted) => {
-                return Ok(Box::from_raw_in(casted, gotten_rc.1))
+                Ok(Box::from_raw_in(casted, gotten.1))
             }
             Err(err) => {
-                Err( CastErrorWith {
-                    with: Box::from_raw_in(gotten_rc.0, gotten_rc.1),
-                    error: err
-                })
+                Err(CastErrorWith::new(err, Box::from_raw_in(gotten.0, gotten.1)))
             }
         }
     }
 }
+
+#[inline]
+pub fn trait_cross_cast_arc<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Arc<From, A>) -> Result<Arc<TTo, A>, CastErrorWith<Arc<From, A>>> {
+    cast_arc_with::<TTo, From, A>(from, None)
+}
+
+/// [`trait_cross_cast_arc`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_arc_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Arc<From, A>, registry: &Registry) -> Result<Arc<TTo, A>, CastErrorWith<Arc<From, A>>> {
+    cast_arc_with::<TTo, From, A>(from, Some(registry))
+}
+
 #[inline]
-pub fn trait_cross_cast_arc<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Arc<From, A>) -> Result<Arc<TTo, A>, CastErrorWith<Arc<From,A>>> {
+fn cast_arc_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Arc<From, A>, registry: Option<&Registry>) -> Result<Arc<TTo, A>, CastErrorWith<Arc<From, A>>> {
     unsafe {
-        let gotten_rc :  (*const From, A)  = Arc::into_raw_with_allocator(from);
-        let as_ref = &*gotten_rc.0;
-        let casted = trait_cross_cast_ref::<TTo>(as_ref);
-        match casted {
+        let gotten: (*const From, A) = Arc::into_raw_with_allocator(from);
+        match cast_ref_with::<TTo>(&*gotten.0, AutoTraits::of::<From>(), registry) {
             Ok(casted) => {
-                return Ok(Arc::from_raw_in(casted, gotten_rc.1))
+                Ok(Arc::from_raw_in(casted, gotten.1))
             }
             Err(err) => {
-                Err( CastErrorWith {
-                    with: Arc::from_raw_in(gotten_rc.0, gotten_rc.1),
-                    error: err
-                })
+                Err(CastErrorWith::new(err, Arc::from_raw_in(gotten.0, gotten.1)))
             }
         }
     }
 }
+
+/// Casts a `std::sync::Weak` without upgrading it.
+///
+/// The concrete type is read from the vtable of the weak pointer, so this also works when the
+/// strong count is zero or when the weak was created with `Weak::new`.
+#[inline]
+pub fn trait_cross_cast_arc_weak<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: sync::Weak<From, A>) -> Result<sync::Weak<TTo, A>, CastErrorWith<sync::Weak<From, A>>> {
+    cast_arc_weak_with::<TTo, From, A>(from, None)
+}
+
+/// [`trait_cross_cast_arc_weak`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_arc_weak_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: sync::Weak<From, A>, registry: &Registry) -> Result<sync::Weak<TTo, A>, CastErrorWith<sync::Weak<From, A>>> {
+    cast_arc_weak_with::<TTo, From, A>(from, Some(registry))
+}
+
 #[inline]
-pub fn trait_cross_cast_ref<'a,TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>>(from: &dyn Castable) -> Result<&'a TTo, CastError> {
+fn cast_arc_weak_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: sync::Weak<From, A>, registry: Option<&Registry>) -> Result<sync::Weak<TTo, A>, CastErrorWith<sync::Weak<From, A>>> {
     unsafe {
-        let vtable = get_vtable::<TTo>(from);
-        match vtable {
-            Ok(vtable) => {
-                let gotten: *const TTo = ptr::from_raw_parts(from as *const dyn Castable as *const (), generic_transmute(vtable));
-                Ok(&*gotten)
+        let gotten: (*const From, A) = sync::Weak::into_raw_with_allocator(from);
+        match cast_ptr_with::<TTo, From>(gotten.0, registry) {
+            Ok(casted) => {
+                Ok(sync::Weak::from_raw_in(casted, gotten.1))
             }
             Err(err) => {
-                Err(err)
+                Err(CastErrorWith::new(err, sync::Weak::from_raw_in(gotten.0, gotten.1)))
             }
         }
     }
 }
 
+/// Casts a shared reference to a trait object into a reference to another trait object
+/// implemented by the same concrete type.
+///
+/// The returned reference borrows from `from`, so it can not outlive the object it points to:
+///
+/// ```compile_fail,E0597
+/// # use iza_trait_cast::cast_fns::trait_cross_cast_ref;
+/// # use iza_trait_cast::trait_registry::Castable;
+/// # trait Base: Castable {}
+/// # trait Child: Base {}
+/// # struct Foo;
+/// # impl Base for Foo {}
+/// # impl Child for Foo {}
+/// let child;
+/// {
+///     let foo = Foo;
+///     let base: &dyn Base = &foo;
+///     child = trait_cross_cast_ref::<dyn Child>(base).unwrap();
+/// }
+/// let _keep_alive: &dyn Child = child;
+/// ```
+///
+/// ```compile_fail,E0515
+/// # use iza_trait_cast::cast_fns::trait_cross_cast_ref;
+/// # use iza_trait_cast::trait_registry::Castable;
+/// # trait Base: Castable {}
+/// # trait Child: Base {}
+/// # struct Foo;
+/// # impl Base for Foo {}
+/// # impl Child for Foo {}
+/// fn escape() -> &'static dyn Child {
+///     let foo = Foo;
+///     trait_cross_cast_ref::<dyn Child>(&foo as &dyn Base).unwrap()
+/// }
+/// ```
+///
+/// ```compile_fail,E0505
+/// # use iza_trait_cast::cast_fns::trait_cross_cast_ref;
+/// # use iza_trait_cast::trait_registry::Castable;
+/// # trait Base: Castable {}
+/// # trait Child: Base {}
+/// # struct Foo;
+/// # impl Base for Foo {}
+/// # impl Child for Foo {}
+/// let base: Box<dyn Base> = Box::new(Foo);
+/// let child = trait_cross_cast_ref::<dyn Child>(&*base).unwrap();
+/// drop(base);
+/// let _use_after_free: &dyn Child = child;
+/// ```
+#[inline]
+pub fn trait_cross_cast_ref<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>>(from: &dyn Castable) -> Result<&TTo, CastError> {
+    cast_ref_with::<TTo>(from, AutoTraits::NONE, None)
+}
+
+/// [`trait_cross_cast_ref`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_ref_in<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>>(from: &'a dyn Castable, registry: &Registry) -> Result<&'a TTo, CastError> {
+    cast_ref_with::<TTo>(from, AutoTraits::NONE, Some(registry))
+}
+
+#[inline]
+fn cast_ref_with<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>>(from: &'a dyn Castable, carried: AutoTraits, registry: Option<&Registry>) -> Result<&'a TTo, CastError> {
+    cast_reference!(TTo, from, carried, registry).map(|casted| unsafe { &*casted })
+}
+
+/// Casts a mutable reference to a trait object into a mutable reference to another trait object
+/// implemented by the same concrete type.
+///
+/// The returned reference reborrows `from` for its whole lifetime, so the original reference can
+/// not be used while the cast one is alive, and the cast one can not outlive the object:
+///
+/// ```compile_fail,E0499
+/// # use iza_trait_cast::cast_fns::trait_cross_cast_mut;
+/// # use iza_trait_cast::trait_registry::Castable;
+/// # trait Base: Castable {}
+/// # trait Child: Base {}
+/// # struct Foo;
+/// # impl Base for Foo {}
+/// # impl Child for Foo {}
+/// let mut foo = Foo;
+/// let base: &mut dyn Base = &mut foo;
+/// let child = trait_cross_cast_mut::<dyn Child>(base).unwrap();
+/// let _aliasing: &mut dyn Base = &mut *base;
+/// let _still_alive: &mut dyn Child = child;
+/// ```
+///
+/// ```compile_fail,E0515
+/// # use iza_trait_cast::cast_fns::trait_cross_cast_mut;
+/// # use iza_trait_cast::trait_registry::Castable;
+/// # trait Base: Castable {}
+/// # trait Child: Base {}
+/// # struct Foo;
+/// # impl Base for Foo {}
+/// # impl Child for Foo {}
+/// fn escape() -> &'static mut dyn Child {
+///     let mut foo = Foo;
+///     trait_cross_cast_mut::<dyn Child>(&mut foo as &mut dyn Base).unwrap()
+/// }
+/// ```
+#[inline]
+pub fn trait_cross_cast_mut<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>>(from: &mut dyn  Castable) -> Result<&mut TTo, CastError> {
+    cast_mut_with::<TTo>(from, AutoTraits::NONE, None)
+}
+
+/// [`trait_cross_cast_mut`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_mut_in<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>>(from: &'a mut dyn Castable, registry: &Registry) -> Result<&'a mut TTo, CastError> {
+    cast_mut_with::<TTo>(from, AutoTraits::NONE, Some(registry))
+}
+
+#[inline]
+fn cast_mut_with<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>>(from: &'a mut dyn Castable, carried: AutoTraits, registry: Option<&Registry>) -> Result<&'a mut TTo, CastError> {
+    cast_reference!(TTo, from, carried, registry).map(|casted| unsafe { &mut *(casted as *mut TTo) })
+}
+
+/// Casts a raw trait object pointer into a pointer to another trait object implemented by the
+/// same concrete type.
+///
+/// # Safety
+/// `from` must carry valid metadata (a vtable for `From` of its concrete type). Its data pointer
+/// is never dereferenced, so it may point to uninitialised, partially initialised or freed memory.
+#[inline]
+pub unsafe fn trait_cross_cast_ptr<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: *const From) -> Result<*const TTo, CastError> {
+    unsafe { cast_ptr_with::<TTo, From>(from, None) }
+}
+
+/// [`trait_cross_cast_ptr`], looking only in `registry`.
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_ptr`].
+#[inline]
+pub unsafe fn trait_cross_cast_ptr_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: *const From, registry: &Registry) -> Result<*const TTo, CastError> {
+    unsafe { cast_ptr_with::<TTo, From>(from, Some(registry)) }
+}
+
 #[inline]
-pub fn trait_cross_cast_mut<'a,TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>>(from: &mut dyn  Castable) -> Result<&'a mut TTo, CastError> {
+unsafe fn cast_ptr_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: *const From, registry: Option<&Registry>) -> Result<*const TTo, CastError> {
     unsafe {
-        let vtable = get_vtable::<TTo>(from);
+        let vtable = get_vtable::<TTo>(from, AutoTraits::of::<From>(), registry);
         match vtable {
             Ok(vtable) => {
-                let gotten: *mut TTo = ptr::from_raw_parts_mut(from as *mut dyn Castable as *mut (), generic_transmute(vtable));
-                Ok(&mut *gotten)
+                Ok(ptr::from_raw_parts(from as *const (), generic_transmute(vtable)))
             }
             Err(err) => {
                 Err(err)
@@ -133,3 +326,441 @@ pub fn trait_cross_cast_mut<'a,TTo: ?Sized + 'static + Pointee<Metadata=DynMetad
     }
 }
 
+/// Mutable version of [`trait_cross_cast_ptr`].
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_ptr`].
+#[inline]
+pub unsafe fn trait_cross_cast_ptr_mut<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: *mut From) -> Result<*mut TTo, CastError> {
+    unsafe { cast_ptr_mut_with::<TTo, From>(from, None) }
+}
+
+/// [`trait_cross_cast_ptr_mut`], looking only in `registry`.
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_ptr_mut`].
+#[inline]
+pub unsafe fn trait_cross_cast_ptr_mut_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: *mut From, registry: &Registry) -> Result<*mut TTo, CastError> {
+    unsafe { cast_ptr_mut_with::<TTo, From>(from, Some(registry)) }
+}
+
+#[inline]
+unsafe fn cast_ptr_mut_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: *mut From, registry: Option<&Registry>) -> Result<*mut TTo, CastError> {
+    unsafe {
+        cast_ptr_with::<TTo, From>(from, registry).map(|casted| casted as *mut TTo)
+    }
+}
+
+/// [`NonNull`] version of [`trait_cross_cast_ptr`].
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_ptr`].
+#[inline]
+pub unsafe fn trait_cross_cast_non_null<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: NonNull<From>) -> Result<NonNull<TTo>, CastError> {
+    unsafe { cast_non_null_with::<TTo, From>(from, None) }
+}
+
+/// [`trait_cross_cast_non_null`], looking only in `registry`.
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_non_null`].
+#[inline]
+pub unsafe fn trait_cross_cast_non_null_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: NonNull<From>, registry: &Registry) -> Result<NonNull<TTo>, CastError> {
+    unsafe { cast_non_null_with::<TTo, From>(from, Some(registry)) }
+}
+
+#[inline]
+unsafe fn cast_non_null_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: NonNull<From>, registry: Option<&Registry>) -> Result<NonNull<TTo>, CastError> {
+    unsafe {
+        cast_ptr_mut_with::<TTo, From>(from.as_ptr(), registry).map(|casted| NonNull::new_unchecked(casted))
+    }
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_box<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Pin<Box<From, A>>) -> Result<Pin<Box<TTo, A>>, CastErrorWith<Pin<Box<From, A>>>> {
+    cast_pin_box_with::<TTo, From, A>(from, None)
+}
+
+/// [`trait_cross_cast_pin_box`], looking only in `registry`.
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_box_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Pin<Box<From, A>>, registry: &Registry) -> Result<Pin<Box<TTo, A>>, CastErrorWith<Pin<Box<From, A>>>> {
+    cast_pin_box_with::<TTo, From, A>(from, Some(registry))
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+fn cast_pin_box_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Pin<Box<From, A>>, registry: Option<&Registry>) -> Result<Pin<Box<TTo, A>>, CastErrorWith<Pin<Box<From, A>>>> {
+    unsafe { cast_pinned(from, |from| cast_box_with::<TTo, From, A>(from, registry)) }
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_rc<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Pin<Rc<From, A>>) -> Result<Pin<Rc<TTo, A>>, CastErrorWith<Pin<Rc<From, A>>>> {
+    cast_pin_rc_with::<TTo, From, A>(from, None)
+}
+
+/// [`trait_cross_cast_pin_rc`], looking only in `registry`.
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_rc_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Pin<Rc<From, A>>, registry: &Registry) -> Result<Pin<Rc<TTo, A>>, CastErrorWith<Pin<Rc<From, A>>>> {
+    cast_pin_rc_with::<TTo, From, A>(from, Some(registry))
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+fn cast_pin_rc_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Pin<Rc<From, A>>, registry: Option<&Registry>) -> Result<Pin<Rc<TTo, A>>, CastErrorWith<Pin<Rc<From, A>>>> {
+    unsafe { cast_pinned(from, |from| cast_rc_with::<TTo, From, A>(from, registry)) }
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_arc<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Pin<Arc<From, A>>) -> Result<Pin<Arc<TTo, A>>, CastErrorWith<Pin<Arc<From, A>>>> {
+    cast_pin_arc_with::<TTo, From, A>(from, None)
+}
+
+/// [`trait_cross_cast_pin_arc`], looking only in `registry`.
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_arc_in<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Pin<Arc<From, A>>, registry: &Registry) -> Result<Pin<Arc<TTo, A>>, CastErrorWith<Pin<Arc<From, A>>>> {
+    cast_pin_arc_with::<TTo, From, A>(from, Some(registry))
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+fn cast_pin_arc_with<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized, A: Allocator>(from: Pin<Arc<From, A>>, registry: Option<&Registry>) -> Result<Pin<Arc<TTo, A>>, CastErrorWith<Pin<Arc<From, A>>>> {
+    unsafe { cast_pinned(from, |from| cast_arc_with::<TTo, From, A>(from, registry)) }
+}
+
+#[inline]
+pub fn trait_cross_cast_pin_mut<TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: Pin<&mut From>) -> Result<Pin<&mut TTo>, CastErrorWith<Pin<&mut From>>> {
+    cast_pin_mut_with::<TTo, From>(from, None)
+}
+
+/// [`trait_cross_cast_pin_mut`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_pin_mut_in<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: Pin<&'a mut From>, registry: &Registry) -> Result<Pin<&'a mut TTo>, CastErrorWith<Pin<&'a mut From>>> {
+    cast_pin_mut_with::<TTo, From>(from, Some(registry))
+}
+
+#[inline]
+fn cast_pin_mut_with<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: Pin<&'a mut From>, registry: Option<&Registry>) -> Result<Pin<&'a mut TTo>, CastErrorWith<Pin<&'a mut From>>> {
+    unsafe { cast_pinned(from, |from| cast_mut_or_return(from, |from| cast_mut_with::<TTo>(from, AutoTraits::of::<From>(), registry))) }
+}
+
+#[inline]
+pub fn trait_cross_cast_cell_ref<'b, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: Ref<'b, From>) -> Result<Ref<'b, TTo>, CastErrorWith<Ref<'b, From>>> {
+    cast_cell_ref_with::<TTo, From>(from, None)
+}
+
+/// [`trait_cross_cast_cell_ref`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_cell_ref_in<'b, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: Ref<'b, From>, registry: &Registry) -> Result<Ref<'b, TTo>, CastErrorWith<Ref<'b, From>>> {
+    cast_cell_ref_with::<TTo, From>(from, Some(registry))
+}
+
+#[inline]
+fn cast_cell_ref_with<'b, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: Ref<'b, From>, registry: Option<&Registry>) -> Result<Ref<'b, TTo>, CastErrorWith<Ref<'b, From>>> {
+    cast_cell_ref(from, |inner| cast_ref_with::<TTo>(inner, AutoTraits::of::<From>(), registry))
+}
+
+#[inline]
+pub fn trait_cross_cast_cell_ref_mut<'b, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: RefMut<'b, From>) -> Result<RefMut<'b, TTo>, CastErrorWith<RefMut<'b, From>>> {
+    cast_cell_ref_mut_with::<TTo, From>(from, None)
+}
+
+/// [`trait_cross_cast_cell_ref_mut`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_cell_ref_mut_in<'b, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: RefMut<'b, From>, registry: &Registry) -> Result<RefMut<'b, TTo>, CastErrorWith<RefMut<'b, From>>> {
+    cast_cell_ref_mut_with::<TTo, From>(from, Some(registry))
+}
+
+#[inline]
+fn cast_cell_ref_mut_with<'b, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: RefMut<'b, From>, registry: Option<&Registry>) -> Result<RefMut<'b, TTo>, CastErrorWith<RefMut<'b, From>>> {
+    cast_cell_ref_mut(from, |inner| cast_mut_with::<TTo>(inner, AutoTraits::of::<From>(), registry))
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn trait_cross_cast_mutex_guard<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: MutexGuard<'a, From>) -> Result<MappedMutexGuard<'a, TTo>, CastErrorWith<MutexGuard<'a, From>>> {
+    cast_mutex_guard_with::<TTo, From>(from, None)
+}
+
+#[cfg(feature = "std")]
+/// [`trait_cross_cast_mutex_guard`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_mutex_guard_in<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: MutexGuard<'a, From>, registry: &Registry) -> Result<MappedMutexGuard<'a, TTo>, CastErrorWith<MutexGuard<'a, From>>> {
+    cast_mutex_guard_with::<TTo, From>(from, Some(registry))
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn cast_mutex_guard_with<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: MutexGuard<'a, From>, registry: Option<&Registry>) -> Result<MappedMutexGuard<'a, TTo>, CastErrorWith<MutexGuard<'a, From>>> {
+    cast_guard!(MutexGuard::filter_map, from, |inner| cast_mut_with::<TTo>(inner, AutoTraits::of::<From>(), registry))
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn trait_cross_cast_rwlock_read_guard<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: RwLockReadGuard<'a, From>) -> Result<MappedRwLockReadGuard<'a, TTo>, CastErrorWith<RwLockReadGuard<'a, From>>> {
+    cast_rwlock_read_guard_with::<TTo, From>(from, None)
+}
+
+#[cfg(feature = "std")]
+/// [`trait_cross_cast_rwlock_read_guard`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_rwlock_read_guard_in<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: RwLockReadGuard<'a, From>, registry: &Registry) -> Result<MappedRwLockReadGuard<'a, TTo>, CastErrorWith<RwLockReadGuard<'a, From>>> {
+    cast_rwlock_read_guard_with::<TTo, From>(from, Some(registry))
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn cast_rwlock_read_guard_with<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: RwLockReadGuard<'a, From>, registry: Option<&Registry>) -> Result<MappedRwLockReadGuard<'a, TTo>, CastErrorWith<RwLockReadGuard<'a, From>>> {
+    cast_guard!(RwLockReadGuard::filter_map, from, |inner| cast_ref_with::<TTo>(inner, AutoTraits::of::<From>(), registry))
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn trait_cross_cast_rwlock_write_guard<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: RwLockWriteGuard<'a, From>) -> Result<MappedRwLockWriteGuard<'a, TTo>, CastErrorWith<RwLockWriteGuard<'a, From>>> {
+    cast_rwlock_write_guard_with::<TTo, From>(from, None)
+}
+
+#[cfg(feature = "std")]
+/// [`trait_cross_cast_rwlock_write_guard`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_rwlock_write_guard_in<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: RwLockWriteGuard<'a, From>, registry: &Registry) -> Result<MappedRwLockWriteGuard<'a, TTo>, CastErrorWith<RwLockWriteGuard<'a, From>>> {
+    cast_rwlock_write_guard_with::<TTo, From>(from, Some(registry))
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn cast_rwlock_write_guard_with<'a, TTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TTo>>, From: Unsize<dyn Castable> + ?Sized>(from: RwLockWriteGuard<'a, From>, registry: Option<&Registry>) -> Result<MappedRwLockWriteGuard<'a, TTo>, CastErrorWith<RwLockWriteGuard<'a, From>>> {
+    cast_guard!(RwLockWriteGuard::filter_map, from, |inner| cast_mut_with::<TTo>(inner, AutoTraits::of::<From>(), registry))
+}
+
+impl<'a, T: Unsize<dyn Castable> + ?Sized> CrossCast for &'a T {
+    type Output<TTo: CastTarget + ?Sized> = &'a TTo;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<&'a TTo, CastErrorWith<Self>> {
+        cast_ref_with::<TTo>(self, AutoTraits::of::<T>(), None).map_err(|err| CastErrorWith::new(err, self))
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<&'a TTo, CastErrorWith<Self>> {
+        cast_ref_with::<TTo>(self, AutoTraits::of::<T>(), Some(registry)).map_err(|err| CastErrorWith::new(err, self))
+    }
+}
+
+impl<'a, T: Unsize<dyn Castable> + ?Sized> CrossCast for &'a mut T {
+    type Output<TTo: CastTarget + ?Sized> = &'a mut TTo;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<&'a mut TTo, CastErrorWith<Self>> {
+        cast_mut_or_return(self, |from| cast_mut_with::<TTo>(from, AutoTraits::of::<T>(), None))
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<&'a mut TTo, CastErrorWith<Self>> {
+        cast_mut_or_return(self, |from| cast_mut_with::<TTo>(from, AutoTraits::of::<T>(), Some(registry)))
+    }
+}
+
+impl<T: Unsize<dyn Castable> + ?Sized, A: Allocator> CrossCast for Box<T, A> {
+    type Output<TTo: CastTarget + ?Sized> = Box<TTo, A>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Box<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_box::<TTo, T, A>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Box<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_box_in::<TTo, T, A>(self, registry)
+    }
+}
+
+impl<T: Unsize<dyn Castable> + ?Sized, A: Allocator> CrossCast for Rc<T, A> {
+    type Output<TTo: CastTarget + ?Sized> = Rc<TTo, A>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Rc<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_rc::<TTo, T, A>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Rc<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_rc_in::<TTo, T, A>(self, registry)
+    }
+}
+
+impl<T: Unsize<dyn Castable> + ?Sized, A: Allocator> CrossCast for Arc<T, A> {
+    type Output<TTo: CastTarget + ?Sized> = Arc<TTo, A>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Arc<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_arc::<TTo, T, A>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Arc<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_arc_in::<TTo, T, A>(self, registry)
+    }
+}
+
+impl<T: Unsize<dyn Castable> + ?Sized, A: Allocator> CrossCast for rc::Weak<T, A> {
+    type Output<TTo: CastTarget + ?Sized> = rc::Weak<TTo, A>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<rc::Weak<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_rc_weak::<TTo, T, A>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<rc::Weak<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_rc_weak_in::<TTo, T, A>(self, registry)
+    }
+}
+
+impl<T: Unsize<dyn Castable> + ?Sized, A: Allocator> CrossCast for sync::Weak<T, A> {
+    type Output<TTo: CastTarget + ?Sized> = sync::Weak<TTo, A>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<sync::Weak<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_arc_weak::<TTo, T, A>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<sync::Weak<TTo, A>, CastErrorWith<Self>> {
+        trait_cross_cast_arc_weak_in::<TTo, T, A>(self, registry)
+    }
+}
+
+impl<T: Unsize<dyn Castable> + ?Sized, A: Allocator> CrossCast for Pin<Box<T, A>> {
+    type Output<TTo: CastTarget + ?Sized> = Pin<Box<TTo, A>>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Pin<Box<TTo, A>>, CastErrorWith<Self>> {
+        trait_cross_cast_pin_box::<TTo, T, A>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Pin<Box<TTo, A>>, CastErrorWith<Self>> {
+        trait_cross_cast_pin_box_in::<TTo, T, A>(self, registry)
+    }
+}
+
+impl<T: Unsize<dyn Castable> + ?Sized, A: Allocator> CrossCast for Pin<Rc<T, A>> {
+    type Output<TTo: CastTarget + ?Sized> = Pin<Rc<TTo, A>>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Pin<Rc<TTo, A>>, CastErrorWith<Self>> {
+        trait_cross_cast_pin_rc::<TTo, T, A>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Pin<Rc<TTo, A>>, CastErrorWith<Self>> {
+        trait_cross_cast_pin_rc_in::<TTo, T, A>(self, registry)
+    }
+}
+
+impl<T: Unsize<dyn Castable> + ?Sized, A: Allocator> CrossCast for Pin<Arc<T, A>> {
+    type Output<TTo: CastTarget + ?Sized> = Pin<Arc<TTo, A>>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Pin<Arc<TTo, A>>, CastErrorWith<Self>> {
+        trait_cross_cast_pin_arc::<TTo, T, A>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Pin<Arc<TTo, A>>, CastErrorWith<Self>> {
+        trait_cross_cast_pin_arc_in::<TTo, T, A>(self, registry)
+    }
+}
+
+impl<'a, T: Unsize<dyn Castable> + ?Sized> CrossCast for Pin<&'a mut T> {
+    type Output<TTo: CastTarget + ?Sized> = Pin<&'a mut TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Pin<&'a mut TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_pin_mut::<TTo, T>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Pin<&'a mut TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_pin_mut_in::<TTo, T>(self, registry)
+    }
+}
+
+impl<'b, T: Unsize<dyn Castable> + ?Sized> CrossCast for Ref<'b, T> {
+    type Output<TTo: CastTarget + ?Sized> = Ref<'b, TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Ref<'b, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_cell_ref::<TTo, T>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Ref<'b, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_cell_ref_in::<TTo, T>(self, registry)
+    }
+}
+
+impl<'b, T: Unsize<dyn Castable> + ?Sized> CrossCast for RefMut<'b, T> {
+    type Output<TTo: CastTarget + ?Sized> = RefMut<'b, TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<RefMut<'b, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_cell_ref_mut::<TTo, T>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<RefMut<'b, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_cell_ref_mut_in::<TTo, T>(self, registry)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: Unsize<dyn Castable> + ?Sized> CrossCast for MutexGuard<'a, T> {
+    type Output<TTo: CastTarget + ?Sized> = MappedMutexGuard<'a, TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<MappedMutexGuard<'a, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_mutex_guard::<TTo, T>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<MappedMutexGuard<'a, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_mutex_guard_in::<TTo, T>(self, registry)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: Unsize<dyn Castable> + ?Sized> CrossCast for RwLockReadGuard<'a, T> {
+    type Output<TTo: CastTarget + ?Sized> = MappedRwLockReadGuard<'a, TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<MappedRwLockReadGuard<'a, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_rwlock_read_guard::<TTo, T>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<MappedRwLockReadGuard<'a, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_rwlock_read_guard_in::<TTo, T>(self, registry)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: Unsize<dyn Castable> + ?Sized> CrossCast for RwLockWriteGuard<'a, T> {
+    type Output<TTo: CastTarget + ?Sized> = MappedRwLockWriteGuard<'a, TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<MappedRwLockWriteGuard<'a, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_rwlock_write_guard::<TTo, T>(self)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<MappedRwLockWriteGuard<'a, TTo>, CastErrorWith<Self>> {
+        trait_cross_cast_rwlock_write_guard_in::<TTo, T>(self, registry)
+    }
+}
@@ -0,0 +1,89 @@
+// What `cast_fns` and `stable_cast_fns` share: the `CrossCast` trait both offer, and the wrappers
+// around a cast that do not depend on how the vtable is found.
+use core::cell::{Ref, RefMut};
+use core::ops::Deref;
+use core::pin::Pin;
+use crate::cast_error_with::CastErrorWith;
+use crate::trait_registry::{CastTarget, CastError, Registry};
+
+/// Extension trait offering a single `.cast::<dyn Trait>()` method on every pointer type the
+/// `trait_cross_cast_*` functions support. With the `nightly` feature it is implemented for the
+/// pointers of `cast_fns`, to any trait object, and otherwise for those of `stable_cast_fns`, to
+/// a `CastSource`.
+///
+/// On failure the original pointer is handed back inside [`CastErrorWith`], exactly like the
+/// owning free functions do.
+pub trait CrossCast: Sized {
+    /// The pointer type produced when casting to `TTo`.
+    type Output<TTo: CastTarget + ?Sized>;
+
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Self::Output<TTo>, CastErrorWith<Self>>;
+
+    /// Like [`CrossCast::cast`], but only looks in `registry`.
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Self::Output<TTo>, CastErrorWith<Self>>;
+}
+
+// macro, to avoid repeating the guard mapping code. `$map` is the guard's `filter_map`, `$cast`
+// casts the reference inside. The cast view keeps the borrow or lock held, and on failure the
+// closure has remembered why, so the original guard is handed back without ever releasing it.
+macro_rules! cast_guard {
+    ($map:path, $from:expr, $cast:expr) => {
+        {
+            let mut cast_error = None;
+            let mapped = $map($from, |inner| {
+                match ($cast)(inner) {
+                    Ok(casted) => Some(casted),
+                    Err(err) => {
+                        cast_error = Some(err);
+                        None
+                    }
+                }
+            });
+            mapped.map_err(|original| $crate::cast_error_with::CastErrorWith::new(cast_error.expect("the cast only fails through the error branch"), original))
+        }
+    };
+}
+// only the lock guards of `cast_fns` use it directly
+#[cfg(all(feature = "nightly", feature = "std"))]
+pub(crate) use cast_guard;
+
+#[inline]
+pub(crate) fn cast_cell_ref<'b, TTo: ?Sized, From: ?Sized>(from: Ref<'b, From>, cast: impl FnOnce(&From) -> Result<&TTo, CastError>) -> Result<Ref<'b, TTo>, CastErrorWith<Ref<'b, From>>> {
+    cast_guard!(Ref::filter_map, from, cast)
+}
+
+#[inline]
+pub(crate) fn cast_cell_ref_mut<'b, TTo: ?Sized, From: ?Sized>(from: RefMut<'b, From>, cast: impl FnOnce(&mut From) -> Result<&mut TTo, CastError>) -> Result<RefMut<'b, TTo>, CastErrorWith<RefMut<'b, From>>> {
+    cast_guard!(RefMut::filter_map, from, cast)
+}
+
+/// Casts `from` with `cast`, handing it back on failure
+#[inline]
+pub(crate) fn cast_mut_or_return<'a, TTo: ?Sized, From: ?Sized>(from: &'a mut From, cast: impl FnOnce(&'a mut From) -> Result<&'a mut TTo, CastError>) -> Result<&'a mut TTo, CastErrorWith<&'a mut From>> {
+    let from: *mut From = from;
+    unsafe {
+        // the borrow checker can not see that the error branch never overlaps the cast borrow
+        match cast(&mut *from) {
+            Ok(casted) => Ok(casted),
+            Err(err) => Err(CastErrorWith::new(err, &mut *from)),
+        }
+    }
+}
+
+/// Casts the pointer inside a `Pin` with `cast`, pinning the result, or the original pointer on
+/// failure.
+///
+/// # Safety
+/// `cast` must point to the same pointee it is given, which it then must not move, and hand
+/// back the pointer it is given on failure. The casts only change the pointer's metadata, so
+/// unwrapping and rewrapping the `Pin` around them is sound.
+#[inline]
+#[allow(clippy::type_complexity)]
+pub(crate) unsafe fn cast_pinned<P: Deref, Q: Deref>(from: Pin<P>, cast: impl FnOnce(P) -> Result<Q, CastErrorWith<P>>) -> Result<Pin<Q>, CastErrorWith<Pin<P>>> {
+    unsafe {
+        match cast(Pin::into_inner_unchecked(from)) {
+            Ok(casted) => Ok(Pin::new_unchecked(casted)),
+            Err(err) => Err(err.map_with(|with| Pin::new_unchecked(with))),
+        }
+    }
+}
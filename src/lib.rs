@@ -1,24 +1,83 @@
-#![feature(allocator_api)]
-#![feature(specialization)]
-#![feature(ptr_metadata)]
-#![feature(unsize)]
-#![feature(const_trait_impl)]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+#![cfg_attr(feature = "nightly", feature(specialization))]
+#![cfg_attr(feature = "nightly", feature(ptr_metadata))]
+#![cfg_attr(feature = "nightly", feature(unsize))]
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+#![cfg_attr(feature = "nightly", feature(arbitrary_self_types_pointers))]
+#![cfg_attr(all(feature = "std", feature = "nightly"), feature(mapped_lock_guards))]
+#![cfg_attr(feature = "nightly", feature(const_type_name))]
+#![cfg_attr(feature = "nightly", allow(incomplete_features))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 pub mod trait_registry;
+mod sync;
+#[cfg(feature = "nightly")]
 mod handy_functions;
+mod cast_error_with;
+mod cross_cast;
+#[cfg(feature = "nightly")]
 pub mod cast_fns;
+pub mod stable_cast_fns;
+
+// lets the attribute macros refer to `::iza_trait_cast` from inside this crate too
+extern crate self as iza_trait_cast;
+
+// for the registration macros, so crates using them do not need to depend on `linkme`
+#[cfg(feature = "linkme")]
+#[doc(hidden)]
+pub use linkme as __linkme;
+
+/// Registers a struct or enum for the listed traits, see `register_types!`.
+///
+/// Unlike `register_types!`, every listed trait must be implemented by the type:
+///
+/// ```compile_fail,E0277
+/// # use iza_trait_cast::castable;
+/// # use iza_trait_cast::trait_registry::Castable;
+/// trait Base: Castable {}
+/// trait Child: Base {}
+///
+/// #[castable(Base, Child)]
+/// struct Foo;
+/// impl Base for Foo {}
+/// ```
+pub use iza_trait_cast_macros::castable;
 
+/// Registers the (type, trait) pair of the annotated trait impl, see `register_types!`.
+///
+/// Generic impls list the instantiations to register, one `<...>` per instantiation:
+///
+/// ```compile_fail
+/// # use iza_trait_cast::register_impl;
+/// # use iza_trait_cast::trait_registry::Castable;
+/// trait Base: Castable {}
+/// struct Wrapper<T>(T);
+///
+/// #[register_impl]
+/// impl<T: 'static> Base for Wrapper<T> {}
+/// ```
+pub use iza_trait_cast_macros::register_impl;
 
+
+// only the tests use these, through `super::*`
+#[allow(unused_imports)]
 use crate::trait_registry::{Castable};
-use std::any::Any;
-#[cfg(test)]
+#[allow(unused_imports)]
+use core::any::Any;
+#[cfg(all(test, feature = "nightly"))]
 mod tests {
     use std::any::type_name;
 use std::any;
     use std::any::TypeId;
-    use std::rc::Rc;
+    use std::pin::Pin;
+    use std::ptr::NonNull;
+    use std::rc::{self, Rc};
     use std::sync::Arc;
-    use crate::cast_fns::{trait_cross_cast_arc, trait_cross_cast_box, trait_cross_cast_rc, CastErrorWith};
+    use crate::cast_fns::{trait_cross_cast_arc, trait_cross_cast_arc_weak, trait_cross_cast_box, trait_cross_cast_cell_ref, trait_cross_cast_cell_ref_mut, trait_cross_cast_non_null, trait_cross_cast_pin_arc, trait_cross_cast_pin_box, trait_cross_cast_pin_mut, trait_cross_cast_pin_rc, trait_cross_cast_ptr, trait_cross_cast_ptr_mut, trait_cross_cast_ptr_mut_in, trait_cross_cast_rc, trait_cross_cast_rc_weak, trait_cross_cast_ref_in, CastErrorWith, CrossCast};
+    #[cfg(feature = "std")]
+    use crate::cast_fns::{trait_cross_cast_mutex_guard, trait_cross_cast_rwlock_write_guard};
     use crate::trait_registry::{ CastError};
     use super::*;
 
@@ -103,6 +162,7 @@ use std::any;
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn rc_cross_cast_trait_not_implemented_returns_original() {
         let rc_base: Rc<dyn Base> = Rc::new(BaseOnly::new());
 
@@ -121,17 +181,18 @@ use std::any;
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn rc_cross_cast_unregistered_type_returns_original() {
         let rc_base: Rc<dyn Base> = Rc::new(UnregisteredType);
 
         match trait_cross_cast_rc::<dyn Child,_,_>(rc_base) {
-            Err(CastErrorWith { error: CastError::CombinationNotRegistered { type_name, type_id, .. }, with }) => {
+            Err(CastErrorWith { error: CastError::TypeNotRegistered { type_name, type_id, .. }, with }) => {
                 assert_eq!(type_name, std::any::type_name::<UnregisteredType>());
                 assert_eq!(type_id, TypeId::of::<UnregisteredType>());
                 assert_eq!(with.name(), "UnregisteredType");
                 assert_eq!(Rc::strong_count(&with), 1);
             }
-            other => panic!("Expected CombinationNotRegistered"),
+            other => panic!("Expected TypeNotRegistered"),
         }
     }
 
@@ -159,6 +220,7 @@ use std::any;
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn arc_cross_cast_trait_not_implemented_returns_original() {
         let arc_base: Arc<dyn Base> = Arc::new(BaseOnly::new());
 
@@ -176,17 +238,18 @@ use std::any;
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn arc_cross_cast_unregistered_type_returns_original() {
         let arc_base: Arc<dyn Base> = Arc::new(UnregisteredType);
 
         match trait_cross_cast_arc::<dyn Child,_,_>(arc_base) {
-            Err(CastErrorWith { error: CastError::CombinationNotRegistered { type_name, type_id, .. }, with }) => {
+            Err(CastErrorWith { error: CastError::TypeNotRegistered { type_name, type_id, .. }, with }) => {
                 assert_eq!(type_name, std::any::type_name::<UnregisteredType>());
                 assert_eq!(type_id, TypeId::of::<UnregisteredType>());
                 assert_eq!(with.name(), "UnregisteredType");
                 assert_eq!(Arc::strong_count(&with), 1);
             }
-            other => panic!("Expected CombinationNotRegistered"),
+            other => panic!("Expected TypeNotRegistered"),
         }
     }
 
@@ -214,6 +277,7 @@ use std::any;
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn box_cross_cast_trait_not_implemented_returns_original() {
         let bx_base: Box<dyn Base> = Box::new(BaseOnly::new());
 
@@ -230,18 +294,1157 @@ use std::any;
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn box_cross_cast_unregistered_type_returns_original() {
         let bx_base: Box<dyn Base> = Box::new(UnregisteredType);
 
         match trait_cross_cast_box::<dyn Child,_,_>(bx_base) {
-            Err(CastErrorWith { error: CastError::CombinationNotRegistered { type_name, type_id, .. }, with }) => {
+            Err(CastErrorWith { error: CastError::TypeNotRegistered { type_name, type_id, .. }, with }) => {
                 assert_eq!(type_name, std::any::type_name::<UnregisteredType>());
                 assert_eq!(type_id, TypeId::of::<UnregisteredType>());
                 assert_eq!(with.name(), "UnregisteredType");
             }
-            other => panic!("Expected CombinationNotRegistered,"),
+            other => panic!("Expected TypeNotRegistered,"),
+        }
+    }
+    // --- Weak versions ------------------------------------------------------
+
+    #[test]
+    fn rc_weak_cross_cast_success_can_upgrade() {
+        let rc_base: Rc<dyn Base> = Rc::new(TestStruct::new());
+        let weak_base: rc::Weak<dyn Base> = Rc::downgrade(&rc_base);
+
+        match trait_cross_cast_rc_weak::<dyn Child,_,_>(weak_base) {
+            Ok(weak_child) => {
+                let rc_child = weak_child.upgrade().expect("object is still alive");
+                assert_eq!(rc_child.favorite_food(), "Chicken");
+                assert_eq!(Rc::strong_count(&rc_base), 2);
+            }
+            Err(e) => panic!("Weak cross-cast failed: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn rc_weak_cross_cast_works_after_strong_count_reaches_zero() {
+        let rc_base: Rc<dyn Base> = Rc::new(TestStruct::new());
+        let weak_base: rc::Weak<dyn Base> = Rc::downgrade(&rc_base);
+        drop(rc_base);
+
+        let weak_child = trait_cross_cast_rc_weak::<dyn Child,_,_>(weak_base).expect("cast of a dead weak should succeed");
+        assert_eq!(weak_child.strong_count(), 0);
+        assert!(weak_child.upgrade().is_none());
+
+        // a weak that never pointed at an allocation
+        let dangling: rc::Weak<dyn Base> = rc::Weak::<TestStruct>::new();
+        let dangling_child = trait_cross_cast_rc_weak::<dyn Child,_,_>(dangling).expect("cast of a dangling weak should succeed");
+        assert!(dangling_child.upgrade().is_none());
+    }
+
+    #[test]
+    fn rc_weak_cross_cast_trait_not_implemented_returns_original() {
+        let rc_base: Rc<dyn Base> = Rc::new(BaseOnly::new());
+        let weak_base: rc::Weak<dyn Base> = Rc::downgrade(&rc_base);
+
+        match trait_cross_cast_rc_weak::<dyn Child,_,_>(weak_base) {
+            Err(CastErrorWith { error: CastError::TraitNotImplemented { type_id, .. }, with }) => {
+                assert_eq!(type_id, TypeId::of::<BaseOnly>());
+                assert_eq!(with.upgrade().unwrap().name(), "BaseOnly");
+                assert_eq!(Rc::weak_count(&rc_base), 1);
+            }
+            _ => panic!("Expected TraitNotImplemented"),
+        }
+    }
+
+    #[test]
+    fn arc_weak_cross_cast_success_and_after_drop() {
+        let arc_base: Arc<dyn Base + Send + Sync> = Arc::new(TestStruct::new());
+        let weak_base = Arc::downgrade(&arc_base);
+
+        let weak_child = trait_cross_cast_arc_weak::<dyn Child + Send + Sync,_,_>(weak_base).expect("Weak cross-cast failed");
+        assert_eq!(weak_child.upgrade().unwrap().favorite_food(), "Chicken");
+
+        drop(arc_base);
+        let dead_child = weak_child.cast::<dyn Base>().expect("cast of a dead weak should succeed");
+        assert!(dead_child.upgrade().is_none());
+    }
+
+    #[test]
+    fn arc_weak_cross_cast_unregistered_type_returns_original() {
+        let arc_base: Arc<dyn Base> = Arc::new(UnregisteredType);
+        let weak_base = Arc::downgrade(&arc_base);
+
+        match trait_cross_cast_arc_weak::<dyn Child,_,_>(weak_base) {
+            Err(CastErrorWith { error: CastError::TypeNotRegistered { type_id, .. }, with }) => {
+                assert_eq!(type_id, TypeId::of::<UnregisteredType>());
+                assert_eq!(with.upgrade().unwrap().name(), "UnregisteredType");
+            }
+            _ => panic!("Expected TypeNotRegistered"),
+        }
+    }
+
+    // --- Raw pointer versions -----------------------------------------------
+
+    #[test]
+    fn raw_pointer_cross_cast_of_uninitialised_object() {
+        let mut slot = std::mem::MaybeUninit::<TestStruct>::uninit();
+        let raw_base: *mut dyn Base = slot.as_mut_ptr();
+
+        // the object does not exist yet, only the vtable is used
+        let raw_child = unsafe { trait_cross_cast_ptr_mut::<dyn Child,_>(raw_base) }.expect("raw cross-cast failed");
+        assert_eq!(raw_child as *mut (), raw_base as *mut ());
+
+        slot.write(TestStruct::new());
+        assert_eq!(unsafe { &*raw_child }.favorite_food(), "Chicken");
+
+        let const_child = unsafe { trait_cross_cast_ptr::<dyn Child,_>(raw_base as *const dyn Base) }.expect("raw cross-cast failed");
+        assert_eq!(unsafe { &*const_child }.favorite_food(), "Chicken");
+    }
+
+    #[test]
+    fn non_null_cross_cast() {
+        let mut test_instance = TestStruct::new();
+        let base: NonNull<dyn Base> = NonNull::from(&mut test_instance);
+        let child = unsafe { trait_cross_cast_non_null::<dyn Child,_>(base) }.expect("NonNull cross-cast failed");
+        assert_eq!(unsafe { child.as_ref() }.favorite_food(), "Chicken");
+
+        let base_only: NonNull<dyn Base> = NonNull::from(&BaseOnly::new());
+        match unsafe { trait_cross_cast_non_null::<dyn Child,_>(base_only) } {
+            Err(CastError::TraitNotImplemented { type_id, .. }) => assert_eq!(type_id, TypeId::of::<BaseOnly>()),
+            _ => panic!("Expected TraitNotImplemented"),
+        }
+    }
+
+    // --- Pinned versions ----------------------------------------------------
+
+    // Self-referential type: `name` points into `storage`, so it must never move.
+    struct PinnedNode {
+        storage: &'static str,
+        name: *const &'static str,
+        _pinned: std::marker::PhantomPinned,
+    }
+    impl PinnedNode {
+        fn new_boxed() -> std::pin::Pin<Box<PinnedNode>> {
+            let mut node = Box::pin(PinnedNode { storage: "PinnedNode", name: std::ptr::null(), _pinned: std::marker::PhantomPinned });
+            unsafe {
+                let node_mut = node.as_mut().get_unchecked_mut();
+                node_mut.name = &node_mut.storage;
+            }
+            node
+        }
+    }
+    impl Base for PinnedNode {
+        fn name(&self) -> &'static str {
+            unsafe { *self.name }
+        }
+    }
+    impl Child for PinnedNode {
+        fn favorite_food(&self) -> &'static str {
+            assert!(std::ptr::eq(self.name, &self.storage), "node was moved");
+            "Pinned food"
+        }
+    }
+    register_types!{
+        implementors: [PinnedNode],
+        traits: [Base, Child]
+    }
+
+    #[test]
+    fn pin_box_cross_cast_does_not_move_pointee() {
+        let pinned_base: Pin<Box<dyn Base>> = PinnedNode::new_boxed();
+        let addr_before = &*pinned_base as *const dyn Base as *const ();
+
+        match trait_cross_cast_pin_box::<dyn Child,_,_>(pinned_base) {
+            Ok(pinned_child) => {
+                assert_eq!(pinned_child.favorite_food(), "Pinned food");
+                assert_eq!(pinned_child.name(), "PinnedNode");
+                assert_eq!(&*pinned_child as *const dyn Child as *const (), addr_before);
+            }
+            Err(e) => panic!("Pinned Box cross-cast failed: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn pin_box_cross_cast_failure_returns_original_pin() {
+        let pinned_base: Pin<Box<dyn Base>> = Box::pin(BaseOnly::new());
+
+        match trait_cross_cast_pin_box::<dyn Child,_,_>(pinned_base) {
+            Err(CastErrorWith { error: CastError::TraitNotImplemented { .. }, with }) => {
+                let still_pinned: Pin<Box<dyn Base>> = with;
+                assert_eq!(still_pinned.name(), "BaseOnly");
+            }
+            _ => panic!("Expected TraitNotImplemented"),
+        }
+    }
+
+    #[test]
+    fn pin_rc_arc_and_mut_cross_cast() {
+        let pinned_rc: Pin<Rc<dyn Base>> = Rc::pin(TestStruct::new());
+        let pinned_rc_child = trait_cross_cast_pin_rc::<dyn Child,_,_>(pinned_rc).expect("Pinned Rc cross-cast failed");
+        assert_eq!(pinned_rc_child.favorite_food(), "Chicken");
+
+        let pinned_arc: Pin<Arc<dyn Base + Send + Sync>> = Arc::pin(TestStruct::new());
+        let pinned_arc_child = pinned_arc.cast::<dyn Child + Send + Sync>().expect("Pinned Arc cross-cast failed");
+        assert_eq!(pinned_arc_child.favorite_food(), "Chicken");
+
+        let mut node = PinnedNode::new_boxed();
+        let pinned_mut: Pin<&mut dyn Base> = node.as_mut();
+        let pinned_mut_child = trait_cross_cast_pin_mut::<dyn Child,_>(pinned_mut).expect("Pinned &mut cross-cast failed");
+        assert_eq!(pinned_mut_child.favorite_food(), "Pinned food");
+
+        let pinned_unregistered: Pin<Arc<dyn Base>> = Arc::pin(UnregisteredType);
+        match trait_cross_cast_pin_arc::<dyn Child,_,_>(pinned_unregistered) {
+            Err(CastErrorWith { error: CastError::TypeNotRegistered { .. }, with }) => {
+                assert_eq!(with.name(), "UnregisteredType");
+            }
+            _ => panic!("Expected TypeNotRegistered"),
+        }
+    }
+
+    // --- Guard versions -----------------------------------------------------
+
+    #[test]
+    fn refcell_guard_cross_cast_keeps_borrow() {
+        let cell: Rc<std::cell::RefCell<dyn Base>> = Rc::new(std::cell::RefCell::new(TestStruct::new()));
+
+        let child = trait_cross_cast_cell_ref::<dyn Child,_>(cell.borrow()).expect("Ref cross-cast failed");
+        assert_eq!(child.favorite_food(), "Chicken");
+        assert!(cell.try_borrow_mut().is_err(), "the shared borrow must still be held");
+        drop(child);
+
+        let child_mut = trait_cross_cast_cell_ref_mut::<dyn Child,_>(cell.borrow_mut()).expect("RefMut cross-cast failed");
+        assert_eq!(child_mut.favorite_food(), "Chicken");
+        assert!(cell.try_borrow().is_err(), "the mutable borrow must still be held");
+    }
+
+    #[test]
+    fn refcell_guard_cross_cast_failure_returns_original_guard() {
+        let cell: Box<std::cell::RefCell<dyn Base>> = Box::new(std::cell::RefCell::new(BaseOnly::new()));
+
+        match trait_cross_cast_cell_ref_mut::<dyn Child,_>(cell.borrow_mut()) {
+            Err(CastErrorWith { error: CastError::TraitNotImplemented { .. }, with }) => {
+                assert_eq!(with.name(), "BaseOnly");
+                assert!(cell.try_borrow().is_err(), "the original guard must still hold the borrow");
+            }
+            _ => panic!("Expected TraitNotImplemented"),
+        }
+        assert!(cell.try_borrow().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn lock_guard_cross_cast_keeps_lock() {
+        let mutex: Arc<std::sync::Mutex<dyn Base + Send>> = Arc::new(std::sync::Mutex::new(TestStruct::new()));
+        let child = trait_cross_cast_mutex_guard::<dyn Child + Send,_>(mutex.lock().unwrap()).expect("MutexGuard cross-cast failed");
+        assert_eq!(child.favorite_food(), "Chicken");
+        assert!(mutex.try_lock().is_err(), "the lock must still be held");
+        drop(child);
+
+        let rwlock: Box<std::sync::RwLock<dyn Base>> = Box::new(std::sync::RwLock::new(TestStruct::new()));
+        let read_child = rwlock.read().unwrap().cast::<dyn Child>().expect("RwLockReadGuard cross-cast failed");
+        assert_eq!(read_child.favorite_food(), "Chicken");
+        assert!(rwlock.try_write().is_err(), "the read lock must still be held");
+        drop(read_child);
+
+        let write_child = trait_cross_cast_rwlock_write_guard::<dyn Child,_>(rwlock.write().unwrap()).expect("RwLockWriteGuard cross-cast failed");
+        assert_eq!(write_child.favorite_food(), "Chicken");
+        assert!(rwlock.try_read().is_err(), "the write lock must still be held");
+        drop(write_child);
+
+        let unregistered: Box<std::sync::Mutex<dyn Base>> = Box::new(std::sync::Mutex::new(UnregisteredType));
+        match trait_cross_cast_mutex_guard::<dyn Child,_>(unregistered.lock().unwrap()) {
+            Err(CastErrorWith { error: CastError::TypeNotRegistered { .. }, with }) => {
+                assert_eq!(with.name(), "UnregisteredType");
+            }
+            _ => panic!("Expected TypeNotRegistered"),
+        }
+    }
+
+    // --- CrossCast extension trait ----------------------------------------
+
+    #[test]
+    fn cross_cast_trait_works_for_every_pointer_kind() {
+        let test_instance = TestStruct::new();
+        let as_ref: &dyn Base = &test_instance;
+        assert_eq!(as_ref.cast::<dyn Child>().unwrap().favorite_food(), "Chicken");
+
+        let mut test_instance = TestStruct::new();
+        let as_mut: &mut dyn Base = &mut test_instance;
+        assert_eq!(as_mut.cast::<dyn Child>().unwrap().favorite_food(), "Chicken");
+
+        let bx_base: Box<dyn Base> = Box::new(TestStruct::new());
+        assert_eq!(bx_base.cast::<dyn Child>().unwrap().favorite_food(), "Chicken");
+
+        let rc_base: Rc<dyn Base> = Rc::new(TestStruct::new());
+        assert_eq!(rc_base.cast::<dyn Child>().unwrap().favorite_food(), "Chicken");
+
+        let arc_base: Arc<dyn Base> = Arc::new(TestStruct::new());
+        assert_eq!(arc_base.cast::<dyn Child>().unwrap().favorite_food(), "Chicken");
+    }
+
+    #[test]
+    fn cross_cast_trait_failure_returns_original() {
+        let mut base_only = BaseOnly::new();
+        let as_mut: &mut dyn Base = &mut base_only;
+        match as_mut.cast::<dyn Child>() {
+            Err(CastErrorWith { error: CastError::TraitNotImplemented { .. }, with }) => {
+                assert_eq!(with.name(), "BaseOnly");
+            }
+            _ => panic!("Expected TraitNotImplemented"),
+        }
+
+        let rc_base: Rc<dyn Base> = Rc::new(UnregisteredType);
+        match rc_base.cast::<dyn Child>() {
+            Err(CastErrorWith { error: CastError::TypeNotRegistered { .. }, with }) => {
+                assert_eq!(with.name(), "UnregisteredType");
+                assert_eq!(Rc::strong_count(&with), 1);
+            }
+            _ => panic!("Expected TypeNotRegistered"),
+        }
+    }
+
+    // --- Auto traits ----------------------------------------------------------
+
+    // Implements both traits but is neither Send nor Sync.
+    struct NotThreadSafe {
+        _marker: std::marker::PhantomData<*const ()>,
+    }
+    impl Base for NotThreadSafe {
+        fn name(&self) -> &'static str {
+            "NotThreadSafe"
+        }
+    }
+    impl Child for NotThreadSafe {
+        fn favorite_food(&self) -> &'static str {
+            "Nothing"
+        }
+    }
+    register_types!{
+        implementors: [NotThreadSafe],
+        traits: [Child]
+    }
+
+    #[test]
+    fn arc_cross_cast_keeps_send_sync() {
+        let arc_base: Arc<dyn Base + Send + Sync> = Arc::new(TestStruct::new());
+
+        match trait_cross_cast_arc::<dyn Child + Send + Sync,_,_>(arc_base) {
+            Ok(arc_child) => {
+                assert_eq!(arc_child.favorite_food(), "Chicken");
+                let handle = std::thread::spawn(move || arc_child.favorite_food());
+                assert_eq!(handle.join().unwrap(), "Chicken");
+            }
+            Err(e) => panic!("Arc cross-cast failed: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn cross_cast_keeps_single_auto_trait() {
+        let bx_base: Box<dyn Base + Send> = Box::new(TestStruct::new());
+        let bx_child: Box<dyn Child + Send> = bx_base.cast::<dyn Child + Send>().unwrap();
+        assert_eq!(bx_child.favorite_food(), "Chicken");
+
+        let test_instance = TestStruct::new();
+        let as_ref: &(dyn Base + Sync) = &test_instance;
+        let as_child: &(dyn Child + Sync) = as_ref.cast::<dyn Child + Sync>().unwrap();
+        assert_eq!(as_child.favorite_food(), "Chicken");
+    }
+
+    #[test]
+    fn cross_cast_to_auto_trait_the_type_lacks_fails() {
+        let rc_base: Rc<dyn Base> = Rc::new(NotThreadSafe { _marker: std::marker::PhantomData });
+
+        match rc_base.cast::<dyn Child + Send>() {
+            Err(CastErrorWith { error: CastError::TraitNotImplemented { trait_id, type_id, .. }, with }) => {
+                assert_eq!(trait_id, TypeId::of::<dyn Child + Send>());
+                assert_eq!(type_id, TypeId::of::<NotThreadSafe>());
+                assert_eq!(with.name(), "NotThreadSafe");
+            }
+            _ => panic!("Expected TraitNotImplemented"),
         }
     }
+
+    // Casts keep the auto traits of the source, they do not add the ones the concrete type has
+    #[test]
+    fn cross_cast_does_not_add_auto_traits_the_type_has() {
+        let rc_base: Rc<dyn Base> = Rc::new(TestStruct::new());
+        match rc_base.cast::<dyn Child + Send>() {
+            Err(CastErrorWith { error: CastError::AutoTraitsNotCarried { trait_id, type_id, .. }, with }) => {
+                assert_eq!(trait_id, TypeId::of::<dyn Child + Send>());
+                assert_eq!(type_id, TypeId::of::<TestStruct>());
+                assert_eq!(with.name(), "TestStruct");
+            }
+            _ => panic!("Expected AutoTraitsNotCarried"),
+        }
+
+        let arc_base: Arc<dyn Base + Send> = Arc::new(TestStruct::new());
+        assert!(matches!(arc_base.cast::<dyn Child + Send + Sync>(), Err(CastErrorWith { error: CastError::AutoTraitsNotCarried { .. }, .. })));
+        // `dyn Castable` has no auto traits
+        let test_instance = TestStruct::new();
+        assert!(matches!(cast_fns::trait_cross_cast_ref::<dyn Child + Sync>(&test_instance), Err(CastError::AutoTraitsNotCarried { .. })));
+        // queries are about the type, which has them
+        assert!(matches!(trait_registry::implements::<dyn Child + Sync>(&test_instance), Ok(true)));
+    }
+
+    #[test]
+    fn auto_trait_variants_are_found_in_the_entry_of_their_trait() {
+        let mut registry = trait_registry::Registry::new();
+        registry.insert(crate::vtable_entry!(TestStruct, dyn Child));
+        assert_eq!(registry.stats().entries, 1);
+        assert!(matches!(registry.type_implements::<TestStruct, dyn Child + Send + Sync>(), Ok(true)));
+
+        let implementors = trait_registry::implementors_of::<dyn Child + Send>();
+        assert!(implementors.contains(&trait_registry::ImplementorInfo::of::<TestStruct>()));
+        assert!(!implementors.contains(&trait_registry::ImplementorInfo::of::<NotThreadSafe>()));
+    }
+
+    trait Service {
+        fn port(&self) -> u16;
+    }
+    struct HttpService;
+    impl Service for HttpService {
+        fn port(&self) -> u16 {
+            80
+        }
+    }
+    struct LocalService(Rc<()>);
+    impl Service for LocalService {
+        fn port(&self) -> u16 {
+            Rc::strong_count(&self.0) as u16
+        }
+    }
+
+    #[test]
+    fn runtime_registrations_cover_auto_trait_variants() {
+        trait_registry::register_entry(crate::vtable_entry!(HttpService, dyn Service));
+        let http: &(dyn Castable + Send + Sync) = &HttpService;
+        assert_eq!(http.cast::<dyn Service + Send + Sync>().unwrap().port(), 80);
+
+        // the variants of `dyn Service` are known now, so registrations from generic code get them too
+        trait_registry::register::<LocalService, dyn Service>();
+        assert!(matches!(trait_registry::type_implements::<LocalService, dyn Service>(), Ok(true)));
+        assert!(matches!(trait_registry::type_implements::<LocalService, dyn Service + Send>(), Ok(false)));
+
+        // a registry only knows the variants registered with a macro in it, not the global ones
+        let mut registry = trait_registry::Registry::new();
+        registry.register::<HttpService, dyn Service>();
+        assert!(http.cast_in::<dyn Service + Sync>(&registry).is_err());
+        assert!(registry.implementors_of::<dyn Service + Sync>().is_empty());
+        registry.insert(crate::vtable_entry!(LocalService, dyn Service));
+        assert_eq!(http.cast_in::<dyn Service + Sync>(&registry).unwrap().port(), 80);
+        assert_eq!(registry.implementors_of::<dyn Service + Sync>().len(), 1);
+    }
+
+    // --- Implements queries -------------------------------------------------
+
+    #[test]
+    fn implements_query() {
+        let test_instance = TestStruct::new();
+        let base_only = BaseOnly::new();
+        assert!(matches!(trait_registry::implements::<dyn Child>(&test_instance as &dyn Base), Ok(true)));
+        assert!(matches!(trait_registry::implements::<dyn Child>(&base_only as &dyn Base), Ok(false)));
+        assert!(matches!(
+            trait_registry::implements::<dyn Child>(&UnregisteredType as &dyn Base),
+            Err(CastError::TypeNotRegistered { .. })
+        ));
+
+        let as_castable: &dyn Castable = &test_instance;
+        assert!(matches!(as_castable.implements::<dyn Child + Send + Sync>(), Ok(true)));
+        let as_castable: &dyn Castable = &base_only;
+        assert!(matches!(as_castable.implements::<dyn Child>(), Ok(false)));
+    }
+
+    #[test]
+    fn type_implements_query() {
+        assert!(matches!(trait_registry::type_implements::<TestStruct, dyn Child>(), Ok(true)));
+        assert!(matches!(trait_registry::type_implements::<BaseOnly, dyn Child>(), Ok(false)));
+        assert!(matches!(trait_registry::type_implements::<NotThreadSafe, dyn Child + Send>(), Ok(false)));
+        match trait_registry::type_implements::<UnregisteredType, dyn Child>() {
+            Err(CastError::TypeNotRegistered { type_name, .. }) => {
+                assert_eq!(type_name, any::type_name::<UnregisteredType>());
+            }
+            _ => panic!("Expected TypeNotRegistered"),
+        }
+    }
+
+    // --- Reflection ---------------------------------------------------------
+
+    fn trait_info<T: ?Sized + 'static>() -> trait_registry::TraitInfo {
+        trait_registry::TraitInfo { trait_id: TypeId::of::<T>(), trait_name: type_name::<T>() }
+    }
+
+    #[test]
+    fn registered_traits_lists_implemented_and_not_implemented() {
+        let registered = trait_registry::registered_traits_of(&TestStruct::new() as &dyn Base).expect("TestStruct is registered");
+        assert!(registered.implemented.contains(&trait_info::<dyn Base>()));
+        assert!(registered.implemented.contains(&trait_info::<dyn Child>()));
+        assert!(registered.not_implemented.is_empty());
+
+        // the Send/Sync variants are part of their trait's registration
+        let registered = trait_registry::registered_traits_of_type_id(TypeId::of::<NotThreadSafe>()).expect("NotThreadSafe is registered");
+        assert_eq!(registered.implemented, vec![trait_info::<dyn Child>()]);
+        assert!(registered.not_implemented.is_empty());
+
+        let registered = trait_registry::registered_traits_of_type_id(TypeId::of::<BaseOnly>()).expect("BaseOnly is registered");
+        assert!(registered.implemented.is_empty());
+        assert!(registered.not_implemented.contains(&trait_info::<dyn Child>()));
+    }
+
+    #[test]
+    fn registered_traits_of_unregistered_type_is_none() {
+        assert!(trait_registry::registered_traits_of(&UnregisteredType as &dyn Base).is_none());
+    }
+
+    // --- Reverse index ------------------------------------------------------
+
+    trait Exporter: Castable {
+        fn export(&self) -> String;
+    }
+    #[derive(Default)]
+    struct JsonExporter {
+        indent: u8,
+    }
+    impl Exporter for JsonExporter {
+        fn export(&self) -> String {
+            format!("json:{}", self.indent)
+        }
+    }
+    struct CsvExporter(#[allow(dead_code)] char);
+    impl Exporter for CsvExporter {
+        fn export(&self) -> String {
+            "csv".to_string()
+        }
+    }
+    register_types!{
+        implementors: [JsonExporter, CsvExporter, BaseOnly],
+        traits: [Exporter]
+    }
+
+    #[test]
+    fn implementors_of_lists_only_implementing_types() {
+        let implementors = trait_registry::implementors_of::<dyn Exporter>();
+        let names: Vec<&str> = implementors.iter().map(|info| info.type_name).collect();
+        assert_eq!(names, vec![type_name::<CsvExporter>(), type_name::<JsonExporter>()]);
+
+        let csv = &implementors[0];
+        assert_eq!(csv.type_id, TypeId::of::<CsvExporter>());
+        assert_eq!(csv.size, size_of::<CsvExporter>());
+        assert_eq!(csv.align, align_of::<CsvExporter>());
+
+        assert!(trait_registry::implementors_of::<dyn Child>().iter().any(|info| info.type_id == TypeId::of::<TestStruct>()));
+        assert!(trait_registry::implementors_of::<dyn Child + Send>().iter().all(|info| info.type_id != TypeId::of::<NotThreadSafe>()));
+        assert!(trait_registry::implementors_of::<dyn UnregisteredTraitForIndex>().is_empty());
+    }
+
+    trait UnregisteredTraitForIndex {}
+
+    #[test]
+    fn implementors_of_can_construct_default_values() {
+        let implementors = trait_registry::implementors_of::<dyn Exporter>();
+        let exported: Vec<String> = implementors.iter()
+            .filter_map(|info| info.construct())
+            .map(|value| value.cast::<dyn Exporter>().expect("registered as implementing Exporter").export())
+            .collect();
+        assert_eq!(exported, vec!["json:0".to_string()]);
+    }
+
+    // --- Duplicate registrations ----------------------------------------------
+
+    // Registers NotThreadSafe for Child a second time, from another module. The answers agree, so
+    // this is fine under `strict-registration` too.
+    mod duplicate_registration {
+        use super::{Child, NotThreadSafe};
+        crate::register_types!{
+            implementors: [NotThreadSafe],
+            traits: [Child]
+        }
+    }
+
+    #[test]
+    fn diagnostics_report_duplicate_registrations() {
+        let duplicates: Vec<_> = trait_registry::diagnostics().duplicates.iter()
+            .filter(|duplicate| duplicate.implementor.type_id == TypeId::of::<NotThreadSafe>())
+            .collect();
+        assert_eq!(duplicates.len(), 1);
+
+        let child = duplicates.iter().find(|duplicate| duplicate.trait_info.trait_id == TypeId::of::<dyn Child>()).unwrap();
+        assert!(child.answers_agree());
+        let mut module_paths: Vec<&str> = child.registrations.iter().map(|(site, _)| site.module_path).collect();
+        module_paths.sort();
+        assert_eq!(module_paths, vec!["iza_trait_cast::tests", "iza_trait_cast::tests::duplicate_registration"]);
+        assert!(child.registrations.iter().all(|(site, implemented)| *implemented && site.crate_name() == "iza_trait_cast"));
+        assert!(!trait_registry::diagnostics().has_conflicts());
+    }
+
+    // Registers TestStruct for Child, saying it is implemented or not, from the given line
+    fn hand_made_entry(implemented: bool, line: u32) -> trait_registry::VTableMapInstance {
+        let v_table = trait_registry::generate_trait_vtable::<TestStruct, dyn Child>().filter(|_| implemented);
+        // SAFETY: the vtable is TestStruct's for `dyn Child`, or none
+        unsafe {
+            trait_registry::VTableMapInstance::new(
+                trait_registry::ImplementorInfo::of::<TestStruct>(),
+                TypeId::of::<dyn Child>(),
+                type_name::<dyn Child>(),
+                v_table,
+                trait_registry::RegistrationSite::new(module_path!(), file!(), line),
+            )
+        }
+    }
+
+    #[test]
+    fn diagnostics_report_conflicting_answers() {
+        let entries = [
+            hand_made_entry(true, 1),
+            hand_made_entry(false, 2),
+        ];
+        let registry = trait_registry::build_vtable_registry(entries, false);
+        let diagnostics = registry.diagnostics();
+        assert_eq!(diagnostics.duplicates.len(), 1);
+        assert!(!diagnostics.duplicates[0].answers_agree());
+        assert!(diagnostics.has_conflicts());
+        assert_eq!(diagnostics.duplicates[0].registrations.iter().map(|(site, _)| site.line).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicting answers")]
+    fn strict_registry_panics_on_conflicting_duplicate() {
+        let entries = [
+            hand_made_entry(true, 1),
+            hand_made_entry(false, 2),
+        ];
+        trait_registry::build_vtable_registry(entries, true);
+    }
+
+    #[test]
+    fn strict_registry_accepts_agreeing_duplicate() {
+        let entries = [
+            hand_made_entry(true, 1),
+            hand_made_entry(true, 2),
+        ];
+        let registry = trait_registry::build_vtable_registry(entries, true);
+        assert_eq!(registry.diagnostics().duplicates.len(), 1);
+        assert!(!registry.diagnostics().has_conflicts());
+    }
+
+    // --- #[castable] attribute ----------------------------------------------
+
+    #[castable(Base, Child)]
+    struct AttributeStruct;
+    impl Base for AttributeStruct {
+        fn name(&self) -> &'static str {
+            "AttributeStruct"
+        }
+    }
+    impl Child for AttributeStruct {
+        fn favorite_food(&self) -> &'static str {
+            "Attribute food"
+        }
+    }
+
+    #[castable(Child, self::Base)]
+    enum AttributeEnum {
+        Pasta,
+    }
+    impl Base for AttributeEnum {
+        fn name(&self) -> &'static str {
+            "AttributeEnum"
+        }
+    }
+    impl Child for AttributeEnum {
+        fn favorite_food(&self) -> &'static str {
+            match self {
+                AttributeEnum::Pasta => "Pasta",
+            }
+        }
+    }
+
+    #[test]
+    fn castable_attribute_registers_type() {
+        let as_base: &dyn Base = &AttributeStruct;
+        assert_eq!(as_base.cast::<dyn Child>().unwrap().favorite_food(), "Attribute food");
+        let as_child: &(dyn Child + Send + Sync) = &AttributeStruct;
+        assert_eq!(as_child.cast::<dyn Base + Send + Sync>().unwrap().name(), "AttributeStruct");
+
+        let boxed: Box<dyn Base> = Box::new(AttributeEnum::Pasta);
+        let as_child = boxed.cast::<dyn Child>().unwrap();
+        assert_eq!(as_child.favorite_food(), "Pasta");
+        assert_eq!(as_child.cast::<dyn Base>().unwrap().name(), "AttributeEnum");
+    }
+
+    // --- #[register_impl] attribute -----------------------------------------
+
+    trait Handler<E>: Castable {
+        fn handle(&self, event: E) -> String;
+    }
+    struct Click;
+
+    struct ImplStruct;
+    #[register_impl]
+    impl Base for ImplStruct {
+        fn name(&self) -> &'static str {
+            "ImplStruct"
+        }
+    }
+    #[register_impl]
+    impl Handler<Click> for ImplStruct {
+        fn handle(&self, _event: Click) -> String {
+            "clicked".to_string()
+        }
+    }
+
+    struct Buffer<T, const N: usize>([Option<T>; N]);
+    #[register_impl(<u8, 2>, <String, 3>)]
+    impl<T: std::fmt::Debug + 'static, const N: usize> Handler<T> for Buffer<T, N> {
+        fn handle(&self, event: T) -> String {
+            format!("{event:?} into {} slots", self.0.len())
+        }
+    }
+
+    #[test]
+    fn register_impl_registers_exactly_the_annotated_pair() {
+        let as_base: &(dyn Base + Send) = &ImplStruct;
+        assert_eq!(as_base.cast::<dyn Handler<Click>>().unwrap().handle(Click), "clicked");
+        assert_eq!(as_base.cast::<dyn Handler<Click> + Send>().unwrap().handle(Click), "clicked");
+        assert!(matches!(
+            as_base.cast::<dyn Child>(),
+            Err(CastErrorWith { error: CastError::CombinationNotRegistered { .. }, .. })
+        ));
+    }
+
+    #[test]
+    fn register_impl_registers_listed_instantiations() {
+        let bytes: Box<dyn Castable> = Box::new(Buffer::<u8, 2>([None, None]));
+        assert_eq!(bytes.cast::<dyn Handler<u8>>().unwrap().handle(7), "7 into 2 slots");
+
+        let strings: Rc<dyn Castable> = Rc::new(Buffer::<String, 3>([None, None, None]));
+        assert_eq!(strings.cast::<dyn Handler<String>>().unwrap().handle("a".to_string()), "\"a\" into 3 slots");
+
+        // not listed, so not registered
+        let unlisted: &dyn Castable = &Buffer::<u8, 3>([None, None, None]);
+        assert!(matches!(unlisted.cast::<dyn Handler<u8>>(), Err(CastErrorWith { error: CastError::TypeNotRegistered { .. }, .. })));
+    }
+
+    // --- Generic traits and full trait object types ---------------------------
+
+    struct Counter(u32);
+    impl Iterator for Counter {
+        type Item = u32;
+        fn next(&mut self) -> Option<u32> {
+            self.0 += 1;
+            Some(self.0)
+        }
+    }
+    struct Submit;
+
+    struct Widget;
+    impl Handler<Click> for Widget {
+        fn handle(&self, _event: Click) -> String {
+            "widget clicked".to_string()
+        }
+    }
+    impl Handler<Submit> for Widget {
+        fn handle(&self, _event: Submit) -> String {
+            "widget submitted".to_string()
+        }
+    }
+
+    register_types!{
+        implementors: [Widget, Counter, fn(&str) -> bool],
+        traits: [
+            Handler<Click>,
+            dyn Handler<Submit> + Send,
+            Iterator<Item = u32>,
+            dyn Fn(&str) -> bool,
+        ]
+    }
+
+    #[test]
+    fn register_generic_trait_path() {
+        let widget: &(dyn Castable + Send + Sync) = &Widget;
+        assert_eq!(widget.cast::<dyn Handler<Click>>().unwrap().handle(Click), "widget clicked");
+        assert_eq!(widget.cast::<dyn Handler<Click> + Send + Sync>().unwrap().handle(Click), "widget clicked");
+        assert!(matches!(
+            (&Counter(0) as &dyn Castable).cast::<dyn Handler<Click>>(),
+            Err(CastErrorWith { error: CastError::TraitNotImplemented { .. }, .. })
+        ));
+    }
+
+    #[test]
+    fn register_full_trait_object_type_exactly() {
+        let widget: &(dyn Castable + Send + Sync) = &Widget;
+        assert_eq!(widget.cast::<dyn Handler<Submit> + Send>().unwrap().handle(Submit), "widget submitted");
+        assert_eq!(widget.cast::<dyn Handler<Submit> + Send + Sync>().unwrap().handle(Submit), "widget submitted");
+        // the written trait object type, but not with fewer auto traits
+        assert!(matches!(
+            widget.cast::<dyn Handler<Submit>>(),
+            Err(CastErrorWith { error: CastError::CombinationNotRegistered { .. }, .. })
+        ));
+    }
+
+    #[test]
+    fn register_associated_type_binding() {
+        let mut counter = Counter(0);
+        let as_castable: &mut dyn Castable = &mut counter;
+        let as_iterator = as_castable.cast::<dyn Iterator<Item = u32>>().unwrap();
+        assert_eq!(as_iterator.next(), Some(1));
+        assert_eq!(as_iterator.next(), Some(2));
+    }
+
+    #[test]
+    fn register_fn_trait() {
+        let is_empty: fn(&str) -> bool = str::is_empty;
+        let as_castable: &dyn Castable = &is_empty;
+        let as_fn = as_castable.cast::<dyn Fn(&str) -> bool>().unwrap();
+        assert!(as_fn(""));
+        assert!(!as_fn("text"));
+        assert!(matches!(
+            (&Widget as &dyn Castable).cast::<dyn Fn(&str) -> bool>(),
+            Err(CastErrorWith { error: CastError::TraitNotImplemented { .. }, .. })
+        ));
+    }
+
+    // --- Generic implementor patterns ----------------------------------------
+
+    struct Wrapper<T>(T);
+    impl<T: 'static> Base for Wrapper<T> {
+        fn name(&self) -> &'static str {
+            type_name::<T>()
+        }
+    }
+    impl<T: std::fmt::Debug + 'static> Exporter for Wrapper<T> {
+        fn export(&self) -> String {
+            format!("{:?}", self.0)
+        }
+    }
+
+    struct RingBuffer<const N: usize>;
+    impl<const N: usize> Base for RingBuffer<N> {
+        fn name(&self) -> &'static str {
+            "RingBuffer"
+        }
+    }
+
+    struct Grid<T, const W: usize>([T; W]);
+    impl<T: 'static, const W: usize> Base for Grid<T, W> {
+        fn name(&self) -> &'static str {
+            "Grid"
+        }
+    }
+
+    register_types!{
+        implementors: [
+            Wrapper<T> for T in (u8, String, TestStruct),
+            RingBuffer<N> for const N: usize in (16, 32, 64),
+            Grid<T, W> for T in (u8, i64) for const W: usize in (1, 2),
+            CsvExporter,
+        ],
+        traits: [Base]
+    }
+
+    #[test]
+    fn generic_implementor_pattern_registers_each_instantiation() {
+        let wrapped: Box<dyn Castable> = Box::new(Wrapper(7u8));
+        assert_eq!(wrapped.cast::<dyn Base>().unwrap().name(), "u8");
+        let wrapped: &dyn Castable = &Wrapper(TestStruct::new());
+        assert_eq!(wrapped.cast::<dyn Base>().unwrap().name(), type_name::<TestStruct>());
+        assert!(matches!(trait_registry::type_implements::<Wrapper<String>, dyn Base + Send + Sync>(), Ok(true)));
+        assert!(matches!(trait_registry::type_implements::<Wrapper<u16>, dyn Base>(), Err(CastError::TypeNotRegistered { .. })));
+        // Exporter was not listed for the pattern
+        assert!(matches!(trait_registry::type_implements::<Wrapper<u8>, dyn Exporter>(), Err(CastError::CombinationNotRegistered { .. })));
+
+        let ring: &dyn Castable = &RingBuffer::<32>;
+        assert_eq!(ring.cast::<dyn Base>().unwrap().name(), "RingBuffer");
+        assert!(matches!(trait_registry::type_implements::<RingBuffer<64>, dyn Base>(), Ok(true)));
+        assert!(matches!(trait_registry::type_implements::<RingBuffer<8>, dyn Base>(), Err(CastError::TypeNotRegistered { .. })));
+
+        // plain implementors can follow patterns
+        assert!(matches!(trait_registry::type_implements::<CsvExporter, dyn Base>(), Ok(false)));
+    }
+
+    #[test]
+    fn generic_implementor_pattern_registers_cartesian_product() {
+        assert!(matches!(trait_registry::type_implements::<Grid<u8, 1>, dyn Base>(), Ok(true)));
+        assert!(matches!(trait_registry::type_implements::<Grid<u8, 2>, dyn Base>(), Ok(true)));
+        assert!(matches!(trait_registry::type_implements::<Grid<i64, 1>, dyn Base>(), Ok(true)));
+        assert!(matches!(trait_registry::type_implements::<Grid<i64, 2>, dyn Base>(), Ok(true)));
+        assert!(matches!(trait_registry::type_implements::<Grid<i64, 3>, dyn Base>(), Err(CastError::TypeNotRegistered { .. })));
+        let names: Vec<&str> = trait_registry::implementors_of::<dyn Base>().iter().map(|info| info.type_name).collect();
+        assert!(names.contains(&type_name::<Grid<i64, 2>>()));
+    }
+
+    // --- Runtime registration -----------------------------------------------
+
+    trait Plugin {
+        fn id(&self) -> usize;
+    }
+
+    struct RuntimePlugin<const N: usize>;
+    impl<const N: usize> Plugin for RuntimePlugin<N> {
+        fn id(&self) -> usize {
+            N
+        }
+    }
+
+    struct RuntimeOnly;
+
+    #[test]
+    fn runtime_registration_enables_casts() {
+        let plugin: &dyn Castable = &RuntimePlugin::<0>;
+        assert!(matches!(plugin.cast::<dyn Plugin>(), Err(CastErrorWith { error: CastError::TypeNotRegistered { .. }, .. })));
+
+        trait_registry::register::<RuntimePlugin<0>, dyn Plugin>();
+        trait_registry::register::<RuntimeOnly, dyn Plugin>();
+        assert_eq!(plugin.cast::<dyn Plugin>().unwrap().id(), 0);
+        assert!(matches!(trait_registry::type_implements::<RuntimeOnly, dyn Plugin>(), Ok(false)));
+        // `dyn Plugin` is never registered with a macro, so `dyn Plugin + Send` is not known
+        assert!(matches!(trait_registry::type_implements::<RuntimePlugin<0>, dyn Plugin + Send>(), Err(CastError::CombinationNotRegistered { .. })));
+
+        let registered = trait_registry::registered_traits_of(plugin).unwrap();
+        assert_eq!(registered.implemented, vec![trait_info::<dyn Plugin>()]);
+        assert!(trait_registry::implementors_of::<dyn Plugin>().contains(&trait_registry::ImplementorInfo::of::<RuntimePlugin<0>>()));
+        assert!(!trait_registry::implementors_of::<dyn Plugin>().contains(&trait_registry::ImplementorInfo::of::<RuntimeOnly>()));
+    }
+
+    #[test]
+    fn runtime_registration_merges_with_static_registry() {
+        // registering a statically registered combination again changes nothing
+        trait_registry::register::<TestStruct, dyn Child>();
+        trait_registry::register::<BaseOnly, dyn Child>();
+        assert!(matches!(trait_registry::type_implements::<BaseOnly, dyn Child>(), Ok(false)));
+
+        // a new trait for a statically registered type shows up next to its static ones
+        trait_registry::register::<TestStruct, dyn Plugin>();
+        let registered = trait_registry::registered_traits_of_type_id(TypeId::of::<TestStruct>()).unwrap();
+        assert!(registered.implemented.contains(&trait_info::<dyn Base>()));
+        assert!(registered.not_implemented.contains(&trait_info::<dyn Plugin>()));
+        assert!(trait_registry::implementors_of::<dyn Base>().contains(&trait_registry::ImplementorInfo::of::<TestStruct>()));
+    }
+
+    #[test]
+    fn concurrent_runtime_registration() {
+        fn register_all() {
+            trait_registry::register::<RuntimePlugin<1>, dyn Plugin>();
+            trait_registry::register::<RuntimePlugin<2>, dyn Plugin>();
+            trait_registry::register::<RuntimePlugin<3>, dyn Plugin>();
+            trait_registry::register::<RuntimePlugin<4>, dyn Plugin>();
+        }
+        let threads: Vec<_> = (0..8).map(|_| std::thread::spawn(register_all)).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let plugins: [Box<dyn Castable>; 4] = [Box::new(RuntimePlugin::<1>), Box::new(RuntimePlugin::<2>), Box::new(RuntimePlugin::<3>), Box::new(RuntimePlugin::<4>)];
+        for (index, plugin) in plugins.into_iter().enumerate() {
+            assert_eq!(plugin.cast::<dyn Plugin>().unwrap().id(), index + 1);
+        }
+        let implementors = trait_registry::implementors_of::<dyn Plugin>();
+        assert_eq!(implementors.iter().filter(|info| info.type_id == TypeId::of::<RuntimePlugin<3>>()).count(), 1);
+    }
+
+    // The runtime registry outgrows its tables several times, while other threads cast with them
+    #[test]
+    fn runtime_registry_grows_while_casting() {
+        type Case = (fn(), fn() -> Option<usize>, usize);
+        macro_rules! cases {
+            ($($n:literal)*) => {
+                [$((
+                    trait_registry::register::<RuntimePlugin<$n>, dyn Plugin> as fn(),
+                    (|| (&RuntimePlugin::<$n> as &dyn Castable).cast::<dyn Plugin>().ok().map(|plugin| plugin.id())) as fn() -> Option<usize>,
+                    $n,
+                )),*]
+            };
+        }
+        let cases: [Case; 32] = cases!(10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32 33 34 35 36 37 38 39 40 41);
+        std::thread::scope(|scope| {
+            for chunk in cases.chunks(8) {
+                scope.spawn(move || {
+                    for (register, cast, id) in chunk {
+                        register();
+                        assert_eq!(cast(), Some(*id));
+                    }
+                });
+            }
+        });
+        assert!(cases.iter().all(|(_, cast, id)| cast() == Some(*id)));
+        let implementors = trait_registry::implementors_of::<dyn Plugin>();
+        assert!(cases.iter().all(|(_, _, id)| implementors.iter().any(|info| info.type_name == format!("{}::RuntimePlugin<{id}>", module_path!()))));
+    }
+
+    // With `inline-cache`, the answers already cached must stay right as registrations come in
+    #[test]
+    fn repeated_casts_see_runtime_registrations() {
+        let plugin: &dyn Castable = &RuntimePlugin::<5>;
+        let test_struct: &dyn Base = &TestStruct::new();
+        for round in 0..4 {
+            if round == 2 {
+                trait_registry::register::<RuntimePlugin<5>, dyn Plugin>();
+                trait_registry::register::<RuntimePlugin<5>, dyn Plugin + Send>();
+            }
+            assert_eq!(plugin.cast::<dyn Plugin>().is_ok(), round >= 2);
+            assert_eq!(test_struct.cast::<dyn Child>().unwrap().favorite_food(), "Chicken");
+            match trait_registry::type_implements::<RuntimePlugin<5>, dyn Plugin + Sync>() {
+                Err(CastError::TypeNotRegistered { .. }) => assert!(round < 2),
+                Err(CastError::CombinationNotRegistered { .. }) => assert!(round >= 2),
+                _ => panic!("dyn Plugin + Sync is never registered"),
+            }
+        }
+        assert_eq!(plugin.cast::<dyn Plugin + Send>().unwrap().id(), 5);
+    }
+
+    // --- Registry instances --------------------------------------------------
+
+    struct TenantPlugin;
+    impl Plugin for TenantPlugin {
+        fn id(&self) -> usize {
+            100
+        }
+    }
+
+    #[test]
+    fn registry_built_by_hand_is_isolated() {
+        let mut tenant_a = trait_registry::Registry::new();
+        tenant_a.register::<TenantPlugin, dyn Plugin>();
+        let tenant_b = trait_registry::Registry::new();
+
+        let plugin: &dyn Castable = &TenantPlugin;
+        assert_eq!(trait_cross_cast_ref_in::<dyn Plugin>(plugin, &tenant_a).unwrap().id(), 100);
+        assert!(matches!(trait_cross_cast_ref_in::<dyn Plugin>(plugin, &tenant_b), Err(CastError::TypeNotRegistered { .. })));
+        // the global registry does not see it either
+        assert!(matches!(plugin.cast::<dyn Plugin>(), Err(CastErrorWith { error: CastError::TypeNotRegistered { .. }, .. })));
+
+        assert!(matches!(tenant_a.type_implements::<TenantPlugin, dyn Plugin>(), Ok(true)));
+        assert_eq!(tenant_a.implementors_of::<dyn Plugin>(), &[trait_registry::ImplementorInfo::of::<TenantPlugin>()]);
+        assert!(tenant_b.implementors_of::<dyn Plugin>().is_empty());
+        assert_eq!(tenant_a.registered_traits_of(plugin).unwrap().implemented, vec![trait_info::<dyn Plugin>()]);
+        assert!(tenant_b.registered_traits_of(plugin).is_none());
+    }
+
+    #[test]
+    fn registry_from_inventory_subset() {
+        let exporters = trait_registry::Registry::from_inventory_filtered(|entry| entry.trait_info() == trait_info::<dyn Exporter>());
+        let json: Box<dyn Castable> = Box::new(JsonExporter::default());
+        let exporter = json.cast_in::<dyn Exporter>(&exporters).unwrap();
+        assert_eq!(exporter.export(), "json:0");
+
+        let test_struct: Arc<dyn Castable> = Arc::new(TestStruct::new());
+        let Err(err) = test_struct.cast_in::<dyn Child>(&exporters) else {
+            panic!("TestStruct as Child is not in the subset");
+        };
+        assert!(matches!(err.error, CastError::TypeNotRegistered { .. }));
+        assert!(exporters.implementors_of::<dyn Base>().is_empty());
+        assert!(matches!(exporters.type_implements::<BaseOnly, dyn Exporter>(), Ok(false)));
+
+        let everything = trait_registry::Registry::from_inventory();
+        assert_eq!(err.with.cast_in::<dyn Child>(&everything).unwrap().name(), "TestStruct");
+        assert_eq!(everything.implementors_of::<dyn Exporter>(), trait_registry::implementors_of::<dyn Exporter>());
+        assert_eq!(everything.diagnostics(), trait_registry::diagnostics());
+    }
+
+    #[test]
+    fn registry_casts_guards_and_pointers() {
+        let mut registry = trait_registry::Registry::new();
+        registry.register::<TestStruct, dyn Child>();
+        registry.register::<TestStruct, dyn Exporter>();
+
+        let cell: Box<std::cell::RefCell<dyn Base>> = Box::new(std::cell::RefCell::new(TestStruct::new()));
+        assert_eq!(cell.borrow().cast_in::<dyn Child>(&registry).unwrap().favorite_food(), "Chicken");
+        assert!(matches!(cell.borrow_mut().cast_in::<dyn Exporter>(&registry), Err(CastErrorWith { error: CastError::TraitNotImplemented { .. }, .. })));
+
+        let weak = Rc::downgrade(&(Rc::new(TestStruct::new()) as Rc<dyn Base>));
+        assert!(weak.cast_in::<dyn Child>(&registry).is_ok());
+        let mut test_struct = TestStruct::new();
+        let raw: *mut dyn Base = &mut test_struct;
+        unsafe {
+            assert!(trait_cross_cast_ptr_mut_in::<dyn Child, dyn Base>(raw, &registry).is_ok());
+            assert!(trait_cross_cast_ptr_mut_in::<dyn Child, dyn Base>(raw, &trait_registry::Registry::new()).is_err());
+        }
+    }
+
+    // --- Eager initialization -------------------------------------------------
+
+    #[test]
+    fn init_builds_the_registry_and_reports_its_size() {
+        let stats = trait_registry::init();
+        assert!(trait_registry::is_initialized());
+        assert_eq!(trait_registry::init(), stats);
+        assert_eq!(stats.build_time.is_some(), cfg!(feature = "std"));
+        assert!(stats.implementors >= trait_registry::implementors_of::<dyn Base>().len());
+        assert!(stats.entries >= stats.implementors);
+        assert!(stats.memory >= stats.entries * 2 * size_of::<TypeId>());
+
+        let mut registry = trait_registry::Registry::new();
+        assert_eq!(registry.stats(), trait_registry::RegistryStats { entries: 0, implementors: 0, build_time: None, memory: 0 });
+        registry.register::<TestStruct, dyn Child>();
+        registry.register::<TestStruct, dyn Base>();
+        assert_eq!((registry.stats().entries, registry.stats().implementors), (2, 1));
+    }
+
+    // --- Error traits ---------------------------------------------------------
+
+    #[test]
+    fn cast_errors_work_as_std_errors() {
+        fn child_food(base: Box<dyn Base>) -> Result<&'static str, Box<dyn std::error::Error>> {
+            Ok(base.cast::<dyn Child>()?.favorite_food())
+        }
+        assert_eq!(child_food(Box::new(TestStruct::new())).unwrap(), "Chicken");
+
+        let err = child_food(Box::new(UnregisteredType)).unwrap_err();
+        assert_eq!(err.to_string(), format!("could not cast '{}'", type_name::<Box<dyn Base>>()));
+        let source = err.source().unwrap().downcast_ref::<CastError>().unwrap();
+        assert!(matches!(source, CastError::TypeNotRegistered { type_name, .. } if type_name.ends_with("UnregisteredType")));
+        assert_eq!(source.to_string(), format!("{source:?}"));
+
+        let as_base: &dyn Base = &UnregisteredType;
+        let first = cast_fns::trait_cross_cast_ref::<dyn Child>(as_base).err().unwrap();
+        let second = cast_fns::trait_cross_cast_ref::<dyn Child>(as_base).err().unwrap();
+        assert_eq!(first, second.clone());
+        let CastError::TypeNotRegistered { trait_name, trait_id, type_name, type_id, .. } = first.clone() else {
+            panic!("UnregisteredType is not registered");
+        };
+        assert_ne!(first, CastError::TraitNotImplemented { trait_name, trait_id, type_name, type_id });
+        assert_eq!(std::collections::HashSet::from([first, second]).len(), 1);
+    }
+
+    #[test]
+    fn not_registered_errors_suggest_the_registration() {
+        let base_only: &dyn Base = &BaseOnly::new();
+        let Err(err) = cast_fns::trait_cross_cast_ref::<dyn Plugin>(base_only) else {
+            panic!("BaseOnly is not registered for Plugin");
+        };
+        assert!(matches!(err, CastError::CombinationNotRegistered { scoped: false, .. }), "BaseOnly is registered for other traits");
+        // the registered traits are listed once each, without their `Send`/`Sync` variants
+        let registered = trait_registry::registered_traits_of_type_id(TypeId::of::<BaseOnly>()).unwrap();
+        let mut registered_traits: Vec<String> = registered.implemented.iter().chain(&registered.not_implemented)
+            .map(|info| format!("'{}'", info.trait_name))
+            .collect();
+        registered_traits.sort();
+        assert!(registered_traits.contains(&format!("'{}'", type_name::<dyn Child>())));
+        let message = err.to_string();
+        assert!(message.contains(&format!("which is registered for {}.", registered_traits.join(", "))));
+        assert!(!message.contains(type_name::<dyn Child + Send>()));
+        assert!(message.ends_with(&format!(
+            "Register it with: register_types!{{ implementors: [{}], traits: [{}] }}", type_name::<BaseOnly>(), type_name::<dyn Plugin>()
+        )));
+
+        let unregistered = trait_registry::type_implements::<UnregisteredType, dyn Child>().unwrap_err();
+        assert!(unregistered.to_string().starts_with(&format!("the underlying concrete type '{}' has not been registered for any trait", type_name::<UnregisteredType>())));
+        assert!(unregistered.to_string().contains(&format!("traits: [{}]", type_name::<dyn Child>())));
+
+        // a registry value is filled with inserts, not with the macros
+        let mut registry = trait_registry::Registry::new();
+        registry.insert(crate::vtable_entry!(BaseOnly, dyn Base));
+        let scoped = registry.type_implements::<BaseOnly, dyn Plugin>().unwrap_err();
+        assert!(matches!(scoped, CastError::CombinationNotRegistered { scoped: true, .. }));
+        let insert = format!("Register it with: registry.insert(vtable_entry!({}, {}))", type_name::<BaseOnly>(), type_name::<dyn Plugin>());
+        assert!(scoped.to_string().ends_with(&insert));
+        // the registered traits are those of the registry looked in, as they were then
+        registry.insert(crate::vtable_entry!(BaseOnly, dyn Child));
+        assert!(scoped.to_string().contains(&format!("in the registry it was looked up in, which is registered for '{}'.", type_name::<dyn Base>())));
+        let CastError::CombinationNotRegistered { registered_traits, .. } = &scoped else { unreachable!() };
+        assert_eq!(registered_traits[..], [type_name::<dyn Base>()]);
+        let scoped_unregistered = registry.type_implements::<UnregisteredType, dyn Plugin>().unwrap_err();
+        assert!(matches!(scoped_unregistered, CastError::TypeNotRegistered { scoped: true, .. }));
+        assert!(scoped_unregistered.to_string().contains("registry.insert(vtable_entry!("));
+    }
+
     // Test that a valid cast returns correct results.
     #[test]
     fn vtable_validity_test() {
@@ -267,15 +1470,16 @@ use std::any;
         }
     }
 
-    // Test that casting without registering the type returns a CombinationNotRegistered error.
+    // Test that casting without registering the type returns a TypeNotRegistered error.
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn unregistered_type_error_test() {
         let as_base: &dyn Base = &UnregisteredType;
 
 
         let result = cast_fns::trait_cross_cast_ref::<dyn Child>(as_base);
         match result {
-            Err(CastError::CombinationNotRegistered { type_name, type_id, ..}) => {
+            Err(CastError::TypeNotRegistered { type_name, type_id, ..}) => {
                 assert_eq!(type_name,any::type_name::<UnregisteredType>(), "Incorrect type name");
                 assert_eq!(type_id, TypeId::of::<UnregisteredType>(), "Incorrect type id");
 
@@ -321,11 +1525,12 @@ use std::any;
         }
     }
 
-    // testing that it gives a CombinationNotRegistered error when checking if a type implements a trait, and that fact has not been registered
+    // testing that it gives a TypeNotRegistered error when checking if a type implements a trait, and registering it for no traits registers nothing
     #[test]
     fn trait_not_registered_test() {
         // Define a trait that we will not register.
         trait UnregisteredTrait: Castable {
+            #[allow(dead_code)]
             fn do_something(&self) -> &'static str;
         }
         // A type that implements UnregisteredTrait.
@@ -347,12 +1552,165 @@ use std::any;
         // Attempt to cast to UnregisteredTrait, expecting an error.
         let result = cast_fns::trait_cross_cast_ref::<dyn UnregisteredTrait>(as_trait);
         match result {
-            Err(CastError::CombinationNotRegistered { trait_name, trait_id: trait_type_id, .. }) => {
+            Err(CastError::TypeNotRegistered { trait_name, trait_id: trait_type_id, .. }) => {
                 assert_eq!(trait_name, type_name::<dyn UnregisteredTrait>(), "Error: Trait name did not match");
                 assert_eq!(trait_type_id, TypeId::of::<dyn UnregisteredTrait>(), "Error: trait type  did not match");
             },
 
-            _ => panic!("Expected a CombinationNotRegistered error because the trait was not registered"),
+            _ => panic!("Expected a TypeNotRegistered error because the trait was not registered"),
+        }
+    }
+}
+// The casts of `stable_cast_fns`, which both backends offer, so these run with and without the
+// `nightly` feature
+#[cfg(test)]
+mod stable_tests {
+    use std::any::TypeId;
+    use std::cell::RefCell;
+    use std::pin::Pin;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use crate::stable_cast_fns::{trait_cross_cast_box, trait_cross_cast_ref, trait_cross_cast_ref_in, CastErrorWith, CrossCast};
+    use crate::trait_registry::{self, Castable, CastError, ImplementorInfo, Registry};
+    use super::*;
+
+    trait Base: Castable {
+        fn name(&self) -> &'static str;
+    }
+    trait Child: Base {
+        fn favorite_food(&self) -> &'static str;
+    }
+    trait Handler<E> {
+        fn handle(&self) -> &'static str;
+    }
+    struct Click;
+
+    #[derive(Default)]
+    struct TestStruct;
+    impl Base for TestStruct {
+        fn name(&self) -> &'static str {
+            "TestStruct"
+        }
+    }
+    impl Child for TestStruct {
+        fn favorite_food(&self) -> &'static str {
+            "Chicken"
+        }
+    }
+    impl Handler<Click> for TestStruct {
+        fn handle(&self) -> &'static str {
+            "click"
+        }
+    }
+
+    struct BaseOnly;
+    impl Base for BaseOnly {
+        fn name(&self) -> &'static str {
+            "BaseOnly"
+        }
+    }
+
+    struct Wrapper<T>(T);
+    impl<T: 'static> Base for Wrapper<T> {
+        fn name(&self) -> &'static str {
+            std::any::type_name::<T>()
+        }
+    }
+
+    register_types!{
+        implementors: [TestStruct, BaseOnly, Wrapper<T> for T in (u8, String)],
+        traits: [Base, Child, dyn Handler<Click> + Send]
+    }
+
+    #[castable(Base)]
+    struct AttributeStruct;
+    impl Base for AttributeStruct {
+        fn name(&self) -> &'static str {
+            "AttributeStruct"
+        }
+    }
+
+    #[test]
+    fn casts_found_through_autoref_specialization() {
+        let test_struct: &dyn Castable = &TestStruct;
+        assert_eq!(trait_cross_cast_ref::<dyn Child>(test_struct).unwrap().favorite_food(), "Chicken");
+        assert_eq!(test_struct.cast::<dyn Handler<Click> + Send>().unwrap().handle(), "click");
+        assert!(matches!(test_struct.cast::<dyn Child + Send>(), Err(CastErrorWith { error: CastError::AutoTraitsNotCarried { .. }, .. })));
+        let thread_safe: &(dyn Castable + Send + Sync) = &TestStruct;
+        assert_eq!(thread_safe.cast::<dyn Child + Send + Sync>().unwrap().favorite_food(), "Chicken");
+        let sync_only: Box<dyn Castable + Sync> = Box::new(TestStruct);
+        assert!(matches!(sync_only.cast::<dyn Child + Send + Sync>(), Err(CastErrorWith { error: CastError::AutoTraitsNotCarried { .. }, .. })));
+
+        let base_only: &dyn Castable = &BaseOnly;
+        assert!(matches!(trait_cross_cast_ref::<dyn Child>(base_only), Err(CastError::TraitNotImplemented { .. })));
+        assert!(matches!(trait_registry::type_implements::<BaseOnly, dyn Child + Send + Sync>(), Ok(false)));
+        assert!(matches!(trait_registry::type_implements::<BaseOnly, dyn Handler<Click>>(), Err(CastError::CombinationNotRegistered { .. })));
+
+        let wrapper: &dyn Castable = &Wrapper(String::new());
+        assert_eq!(wrapper.cast::<dyn Base>().unwrap().name(), "alloc::string::String");
+        let attribute: &dyn Castable = &AttributeStruct;
+        assert_eq!(attribute.cast::<dyn Base>().unwrap().name(), "AttributeStruct");
+    }
+
+    #[test]
+    fn owning_pointer_casts_hand_back_the_original() {
+        let boxed: Box<dyn Base> = Box::new(BaseOnly);
+        let err = match trait_cross_cast_box::<dyn Child>(boxed) {
+            Ok(_) => panic!("BaseOnly does not implement Child"),
+            Err(err) => err,
+        };
+        assert!(matches!(err.error, CastError::TraitNotImplemented { .. }));
+        assert_eq!(err.with.cast::<dyn Base>().unwrap().name(), "BaseOnly");
+
+        let rc: Rc<dyn Castable> = Rc::new(TestStruct);
+        assert_eq!(rc.cast::<dyn Child>().unwrap().favorite_food(), "Chicken");
+        let arc: Arc<dyn Castable> = Arc::new(TestStruct);
+        assert_eq!(arc.cast::<dyn Base>().unwrap().name(), "TestStruct");
+        let pinned: Pin<Box<dyn Castable>> = Box::pin(TestStruct);
+        assert_eq!(pinned.cast::<dyn Child>().unwrap().favorite_food(), "Chicken");
+    }
+
+    #[test]
+    fn mutable_and_cell_casts() {
+        let mut test_struct = TestStruct;
+        let mutable: &mut dyn Castable = &mut test_struct;
+        assert_eq!(mutable.cast::<dyn Child>().unwrap().name(), "TestStruct");
+
+        let cell: Box<RefCell<dyn Castable>> = Box::new(RefCell::new(BaseOnly));
+        let Err(CastErrorWith { error: CastError::TraitNotImplemented { .. }, with: guard }) = cell.borrow().cast::<dyn Child>() else {
+            panic!("BaseOnly does not implement Child");
+        };
+        assert_eq!(guard.cast::<dyn Base>().unwrap().name(), "BaseOnly");
+        assert!(cell.try_borrow_mut().is_ok());
+    }
+
+    #[test]
+    fn default_constructor_found_on_stable() {
+        let implementors = trait_registry::implementors_of::<dyn Child>();
+        assert_eq!(implementors, &[ImplementorInfo::of::<TestStruct>()]);
+        let constructed = implementors[0].construct().unwrap();
+        assert_eq!(constructed.cast::<dyn Child>().unwrap().favorite_food(), "Chicken");
+        assert!(trait_registry::implementors_of::<dyn Base>().iter().any(|info| info.type_id == TypeId::of::<BaseOnly>() && info.construct().is_none()));
+    }
+
+    #[test]
+    fn registry_entries_made_with_vtable_entry() {
+        let mut registry = Registry::new();
+        registry.insert(vtable_entry!(BaseOnly, dyn Base));
+        registry.insert(vtable_entry!(BaseOnly, dyn Child));
+        let base_only: &dyn Castable = &BaseOnly;
+        assert_eq!(trait_cross_cast_ref_in::<dyn Base>(base_only, &registry).unwrap().name(), "BaseOnly");
+        assert!(matches!(registry.type_implements::<BaseOnly, dyn Child>(), Ok(false)));
+        assert!(matches!(registry.type_implements::<TestStruct, dyn Child>(), Err(CastError::TypeNotRegistered { .. })));
+
+        struct RuntimeOnly;
+        impl Base for RuntimeOnly {
+            fn name(&self) -> &'static str {
+                "RuntimeOnly"
+            }
         }
+        trait_registry::register_entry(vtable_entry!(RuntimeOnly, dyn Base));
+        let runtime_only: &dyn Castable = &RuntimeOnly;
+        assert_eq!(runtime_only.cast::<dyn Base>().unwrap().name(), "RuntimeOnly");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,493 @@
+// The casts that build on stable Rust, available with or without the `nightly` feature, so
+// turning it on only adds `cast_fns`. Without `Unsize` and `DynMetadata` the casts can not be
+// generic over the source trait object, so they take `dyn Castable` pointers: upcast to it
+// first, e.g. `&*base as &dyn Castable`. Casts keep the auto traits of the source and never add
+// any, so to reach `dyn Child + Send`, upcast to `dyn Castable + Send` and cast with `CrossCast`,
+// which takes every `CastSource`. Without raw pointer receivers the concrete type can only
+// be read through a live object, so there are no `Weak` casts, and the guard casts are limited to
+// `RefCell`, since mapped lock guards are unstable.
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::cell::{Ref, RefMut};
+use core::pin::Pin;
+use core::ptr::NonNull;
+pub use crate::cast_error_with::CastErrorWith;
+pub use crate::cross_cast::CrossCast;
+use crate::cross_cast::{cast_cell_ref, cast_cell_ref_mut, cast_mut_or_return, cast_pinned};
+use crate::trait_registry::{get_vtable, AutoTraits, CastTarget, Castable, CastError, Registry};
+
+/// The trait object types [`CrossCast`] casts from: `dyn Castable`, with any of `Send` and
+/// `Sync`. A cast keeps those auto traits, so only `dyn Castable + Send + Sync` casts to
+/// `dyn Child + Send + Sync`.
+pub trait CastSource: sealed::Sealed {
+    #[doc(hidden)]
+    const AUTO_TRAITS: AutoTraits;
+    #[doc(hidden)]
+    fn upcast(ptr: *const Self) -> *const dyn Castable;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! cast_source {
+    ($($source:ty => $auto_traits:expr),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $source {}
+            impl CastSource for $source {
+                const AUTO_TRAITS: AutoTraits = $auto_traits;
+
+                #[inline]
+                fn upcast(ptr: *const Self) -> *const dyn Castable {
+                    ptr
+                }
+            }
+        )*
+    };
+}
+
+cast_source! {
+    dyn Castable => AutoTraits::NONE,
+    dyn Castable + Send => AutoTraits::SEND,
+    dyn Castable + Sync => AutoTraits::SYNC,
+    dyn Castable + Send + Sync => AutoTraits::SEND_SYNC,
+}
+
+#[inline]
+pub fn trait_cross_cast_ref<TTo: CastTarget + ?Sized>(from: &dyn Castable) -> Result<&TTo, CastError> {
+    cast_ref_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_ref`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_ref_in<'a, TTo: CastTarget + ?Sized>(from: &'a dyn Castable, registry: &Registry) -> Result<&'a TTo, CastError> {
+    cast_ref_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+fn cast_ref_with<'a, TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: &'a From, registry: Option<&Registry>) -> Result<&'a TTo, CastError> {
+    unsafe { cast_ptr_with::<TTo, From>(from, registry).map(|casted| &*casted) }
+}
+
+#[inline]
+pub fn trait_cross_cast_mut<TTo: CastTarget + ?Sized>(from: &mut dyn Castable) -> Result<&mut TTo, CastError> {
+    cast_mut_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_mut`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_mut_in<'a, TTo: CastTarget + ?Sized>(from: &'a mut dyn Castable, registry: &Registry) -> Result<&'a mut TTo, CastError> {
+    cast_mut_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+fn cast_mut_with<'a, TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: &'a mut From, registry: Option<&Registry>) -> Result<&'a mut TTo, CastError> {
+    unsafe { cast_ptr_mut_with::<TTo, From>(from, registry).map(|casted| &mut *casted) }
+}
+
+/// Casts a raw trait object pointer into a pointer to another trait object implemented by the
+/// same concrete type.
+///
+/// # Safety
+/// `from` must point to a live object, the concrete type is read through it.
+#[inline]
+pub unsafe fn trait_cross_cast_ptr<TTo: CastTarget + ?Sized>(from: *const dyn Castable) -> Result<*const TTo, CastError> {
+    unsafe { cast_ptr_with::<TTo, dyn Castable>(from, None) }
+}
+
+/// [`trait_cross_cast_ptr`], looking only in `registry`.
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_ptr`].
+#[inline]
+pub unsafe fn trait_cross_cast_ptr_in<TTo: CastTarget + ?Sized>(from: *const dyn Castable, registry: &Registry) -> Result<*const TTo, CastError> {
+    unsafe { cast_ptr_with::<TTo, dyn Castable>(from, Some(registry)) }
+}
+
+#[inline]
+unsafe fn cast_ptr_with<TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: *const From, registry: Option<&Registry>) -> Result<*const TTo, CastError> {
+    unsafe {
+        let vtable = get_vtable::<TTo>(&*From::upcast(from), From::AUTO_TRAITS, registry)?;
+        Ok(vtable.attach::<TTo>(from as *const ()))
+    }
+}
+
+/// Mutable version of [`trait_cross_cast_ptr`].
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_ptr`].
+#[inline]
+pub unsafe fn trait_cross_cast_ptr_mut<TTo: CastTarget + ?Sized>(from: *mut dyn Castable) -> Result<*mut TTo, CastError> {
+    unsafe { cast_ptr_mut_with::<TTo, dyn Castable>(from, None) }
+}
+
+/// [`trait_cross_cast_ptr_mut`], looking only in `registry`.
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_ptr`].
+#[inline]
+pub unsafe fn trait_cross_cast_ptr_mut_in<TTo: CastTarget + ?Sized>(from: *mut dyn Castable, registry: &Registry) -> Result<*mut TTo, CastError> {
+    unsafe { cast_ptr_mut_with::<TTo, dyn Castable>(from, Some(registry)) }
+}
+
+#[inline]
+unsafe fn cast_ptr_mut_with<TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: *mut From, registry: Option<&Registry>) -> Result<*mut TTo, CastError> {
+    unsafe { cast_ptr_with::<TTo, From>(from, registry).map(|casted| casted as *mut TTo) }
+}
+
+/// [`NonNull`] version of [`trait_cross_cast_ptr`].
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_ptr`].
+#[inline]
+pub unsafe fn trait_cross_cast_non_null<TTo: CastTarget + ?Sized>(from: NonNull<dyn Castable>) -> Result<NonNull<TTo>, CastError> {
+    unsafe { cast_ptr_mut_with::<TTo, dyn Castable>(from.as_ptr(), None).map(|casted| NonNull::new_unchecked(casted)) }
+}
+
+/// [`trait_cross_cast_non_null`], looking only in `registry`.
+///
+/// # Safety
+/// Same requirements as [`trait_cross_cast_ptr`].
+#[inline]
+pub unsafe fn trait_cross_cast_non_null_in<TTo: CastTarget + ?Sized>(from: NonNull<dyn Castable>, registry: &Registry) -> Result<NonNull<TTo>, CastError> {
+    unsafe { cast_ptr_mut_with::<TTo, dyn Castable>(from.as_ptr(), Some(registry)).map(|casted| NonNull::new_unchecked(casted)) }
+}
+
+#[inline]
+pub fn trait_cross_cast_box<TTo: CastTarget + ?Sized>(from: Box<dyn Castable>) -> Result<Box<TTo>, CastErrorWith<Box<dyn Castable>>> {
+    cast_box_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_box`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_box_in<TTo: CastTarget + ?Sized>(from: Box<dyn Castable>, registry: &Registry) -> Result<Box<TTo>, CastErrorWith<Box<dyn Castable>>> {
+    cast_box_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+fn cast_box_with<TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: Box<From>, registry: Option<&Registry>) -> Result<Box<TTo>, CastErrorWith<Box<From>>> {
+    unsafe {
+        let gotten = Box::into_raw(from);
+        match cast_ptr_mut_with::<TTo, From>(gotten, registry) {
+            Ok(casted) => Ok(Box::from_raw(casted)),
+            Err(err) => Err(CastErrorWith::new(err, Box::from_raw(gotten))),
+        }
+    }
+}
+
+#[inline]
+pub fn trait_cross_cast_rc<TTo: CastTarget + ?Sized>(from: Rc<dyn Castable>) -> Result<Rc<TTo>, CastErrorWith<Rc<dyn Castable>>> {
+    cast_rc_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_rc`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_rc_in<TTo: CastTarget + ?Sized>(from: Rc<dyn Castable>, registry: &Registry) -> Result<Rc<TTo>, CastErrorWith<Rc<dyn Castable>>> {
+    cast_rc_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+fn cast_rc_with<TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: Rc<From>, registry: Option<&Registry>) -> Result<Rc<TTo>, CastErrorWith<Rc<From>>> {
+    unsafe {
+        let gotten = Rc::into_raw(from);
+        match cast_ptr_with::<TTo, From>(gotten, registry) {
+            Ok(casted) => Ok(Rc::from_raw(casted)),
+            Err(err) => Err(CastErrorWith::new(err, Rc::from_raw(gotten))),
+        }
+    }
+}
+
+#[inline]
+pub fn trait_cross_cast_arc<TTo: CastTarget + ?Sized>(from: Arc<dyn Castable>) -> Result<Arc<TTo>, CastErrorWith<Arc<dyn Castable>>> {
+    cast_arc_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_arc`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_arc_in<TTo: CastTarget + ?Sized>(from: Arc<dyn Castable>, registry: &Registry) -> Result<Arc<TTo>, CastErrorWith<Arc<dyn Castable>>> {
+    cast_arc_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+fn cast_arc_with<TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: Arc<From>, registry: Option<&Registry>) -> Result<Arc<TTo>, CastErrorWith<Arc<From>>> {
+    unsafe {
+        let gotten = Arc::into_raw(from);
+        match cast_ptr_with::<TTo, From>(gotten, registry) {
+            Ok(casted) => Ok(Arc::from_raw(casted)),
+            Err(err) => Err(CastErrorWith::new(err, Arc::from_raw(gotten))),
+        }
+    }
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_box<TTo: CastTarget + ?Sized>(from: Pin<Box<dyn Castable>>) -> Result<Pin<Box<TTo>>, CastErrorWith<Pin<Box<dyn Castable>>>> {
+    cast_pin_box_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_pin_box`], looking only in `registry`.
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_box_in<TTo: CastTarget + ?Sized>(from: Pin<Box<dyn Castable>>, registry: &Registry) -> Result<Pin<Box<TTo>>, CastErrorWith<Pin<Box<dyn Castable>>>> {
+    cast_pin_box_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+fn cast_pin_box_with<TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: Pin<Box<From>>, registry: Option<&Registry>) -> Result<Pin<Box<TTo>>, CastErrorWith<Pin<Box<From>>>> {
+    unsafe { cast_pinned(from, |from| cast_box_with::<TTo, From>(from, registry)) }
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_rc<TTo: CastTarget + ?Sized>(from: Pin<Rc<dyn Castable>>) -> Result<Pin<Rc<TTo>>, CastErrorWith<Pin<Rc<dyn Castable>>>> {
+    cast_pin_rc_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_pin_rc`], looking only in `registry`.
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_rc_in<TTo: CastTarget + ?Sized>(from: Pin<Rc<dyn Castable>>, registry: &Registry) -> Result<Pin<Rc<TTo>>, CastErrorWith<Pin<Rc<dyn Castable>>>> {
+    cast_pin_rc_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+fn cast_pin_rc_with<TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: Pin<Rc<From>>, registry: Option<&Registry>) -> Result<Pin<Rc<TTo>>, CastErrorWith<Pin<Rc<From>>>> {
+    unsafe { cast_pinned(from, |from| cast_rc_with::<TTo, From>(from, registry)) }
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_arc<TTo: CastTarget + ?Sized>(from: Pin<Arc<dyn Castable>>) -> Result<Pin<Arc<TTo>>, CastErrorWith<Pin<Arc<dyn Castable>>>> {
+    cast_pin_arc_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_pin_arc`], looking only in `registry`.
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn trait_cross_cast_pin_arc_in<TTo: CastTarget + ?Sized>(from: Pin<Arc<dyn Castable>>, registry: &Registry) -> Result<Pin<Arc<TTo>>, CastErrorWith<Pin<Arc<dyn Castable>>>> {
+    cast_pin_arc_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+#[allow(clippy::type_complexity)]
+fn cast_pin_arc_with<TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: Pin<Arc<From>>, registry: Option<&Registry>) -> Result<Pin<Arc<TTo>>, CastErrorWith<Pin<Arc<From>>>> {
+    unsafe { cast_pinned(from, |from| cast_arc_with::<TTo, From>(from, registry)) }
+}
+
+#[inline]
+pub fn trait_cross_cast_pin_mut<TTo: CastTarget + ?Sized>(from: Pin<&mut dyn Castable>) -> Result<Pin<&mut TTo>, CastErrorWith<Pin<&mut dyn Castable>>> {
+    cast_pin_mut_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_pin_mut`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_pin_mut_in<'a, TTo: CastTarget + ?Sized>(from: Pin<&'a mut dyn Castable>, registry: &Registry) -> Result<Pin<&'a mut TTo>, CastErrorWith<Pin<&'a mut dyn Castable>>> {
+    cast_pin_mut_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+fn cast_pin_mut_with<'a, TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: Pin<&'a mut From>, registry: Option<&Registry>) -> Result<Pin<&'a mut TTo>, CastErrorWith<Pin<&'a mut From>>> {
+    unsafe { cast_pinned(from, |from| cast_mut_or_return(from, |from| cast_mut_with::<TTo, From>(from, registry))) }
+}
+
+#[inline]
+pub fn trait_cross_cast_cell_ref<'b, TTo: CastTarget + ?Sized>(from: Ref<'b, dyn Castable>) -> Result<Ref<'b, TTo>, CastErrorWith<Ref<'b, dyn Castable>>> {
+    cast_cell_ref_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_cell_ref`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_cell_ref_in<'b, TTo: CastTarget + ?Sized>(from: Ref<'b, dyn Castable>, registry: &Registry) -> Result<Ref<'b, TTo>, CastErrorWith<Ref<'b, dyn Castable>>> {
+    cast_cell_ref_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+fn cast_cell_ref_with<'b, TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: Ref<'b, From>, registry: Option<&Registry>) -> Result<Ref<'b, TTo>, CastErrorWith<Ref<'b, From>>> {
+    cast_cell_ref(from, |inner| cast_ref_with::<TTo, From>(inner, registry))
+}
+
+#[inline]
+pub fn trait_cross_cast_cell_ref_mut<'b, TTo: CastTarget + ?Sized>(from: RefMut<'b, dyn Castable>) -> Result<RefMut<'b, TTo>, CastErrorWith<RefMut<'b, dyn Castable>>> {
+    cast_cell_ref_mut_with::<TTo, dyn Castable>(from, None)
+}
+
+/// [`trait_cross_cast_cell_ref_mut`], looking only in `registry`.
+#[inline]
+pub fn trait_cross_cast_cell_ref_mut_in<'b, TTo: CastTarget + ?Sized>(from: RefMut<'b, dyn Castable>, registry: &Registry) -> Result<RefMut<'b, TTo>, CastErrorWith<RefMut<'b, dyn Castable>>> {
+    cast_cell_ref_mut_with::<TTo, dyn Castable>(from, Some(registry))
+}
+
+#[inline]
+fn cast_cell_ref_mut_with<'b, TTo: CastTarget + ?Sized, From: CastSource + ?Sized>(from: RefMut<'b, From>, registry: Option<&Registry>) -> Result<RefMut<'b, TTo>, CastErrorWith<RefMut<'b, From>>> {
+    cast_cell_ref_mut(from, |inner| cast_mut_with::<TTo, From>(inner, registry))
+}
+
+// With the `nightly` feature `cast_fns` implements `CrossCast` for all of these pointers
+
+#[cfg(not(feature = "nightly"))]
+impl<'a, From: CastSource + ?Sized> CrossCast for &'a From {
+    type Output<TTo: CastTarget + ?Sized> = &'a TTo;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<&'a TTo, CastErrorWith<Self>> {
+        cast_ref_with::<TTo, From>(self, None).map_err(|err| CastErrorWith::new(err, self))
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<&'a TTo, CastErrorWith<Self>> {
+        cast_ref_with::<TTo, From>(self, Some(registry)).map_err(|err| CastErrorWith::new(err, self))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<'a, From: CastSource + ?Sized> CrossCast for &'a mut From {
+    type Output<TTo: CastTarget + ?Sized> = &'a mut TTo;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<&'a mut TTo, CastErrorWith<Self>> {
+        cast_mut_or_return(self, |from| cast_mut_with::<TTo, From>(from, None))
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<&'a mut TTo, CastErrorWith<Self>> {
+        cast_mut_or_return(self, |from| cast_mut_with::<TTo, From>(from, Some(registry)))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<From: CastSource + ?Sized> CrossCast for Box<From> {
+    type Output<TTo: CastTarget + ?Sized> = Box<TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Box<TTo>, CastErrorWith<Self>> {
+        cast_box_with::<TTo, From>(self, None)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Box<TTo>, CastErrorWith<Self>> {
+        cast_box_with::<TTo, From>(self, Some(registry))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<From: CastSource + ?Sized> CrossCast for Rc<From> {
+    type Output<TTo: CastTarget + ?Sized> = Rc<TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Rc<TTo>, CastErrorWith<Self>> {
+        cast_rc_with::<TTo, From>(self, None)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Rc<TTo>, CastErrorWith<Self>> {
+        cast_rc_with::<TTo, From>(self, Some(registry))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<From: CastSource + ?Sized> CrossCast for Arc<From> {
+    type Output<TTo: CastTarget + ?Sized> = Arc<TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Arc<TTo>, CastErrorWith<Self>> {
+        cast_arc_with::<TTo, From>(self, None)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Arc<TTo>, CastErrorWith<Self>> {
+        cast_arc_with::<TTo, From>(self, Some(registry))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<From: CastSource + ?Sized> CrossCast for Pin<Box<From>> {
+    type Output<TTo: CastTarget + ?Sized> = Pin<Box<TTo>>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Pin<Box<TTo>>, CastErrorWith<Self>> {
+        cast_pin_box_with::<TTo, From>(self, None)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Pin<Box<TTo>>, CastErrorWith<Self>> {
+        cast_pin_box_with::<TTo, From>(self, Some(registry))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<From: CastSource + ?Sized> CrossCast for Pin<Rc<From>> {
+    type Output<TTo: CastTarget + ?Sized> = Pin<Rc<TTo>>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Pin<Rc<TTo>>, CastErrorWith<Self>> {
+        cast_pin_rc_with::<TTo, From>(self, None)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Pin<Rc<TTo>>, CastErrorWith<Self>> {
+        cast_pin_rc_with::<TTo, From>(self, Some(registry))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<From: CastSource + ?Sized> CrossCast for Pin<Arc<From>> {
+    type Output<TTo: CastTarget + ?Sized> = Pin<Arc<TTo>>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Pin<Arc<TTo>>, CastErrorWith<Self>> {
+        cast_pin_arc_with::<TTo, From>(self, None)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Pin<Arc<TTo>>, CastErrorWith<Self>> {
+        cast_pin_arc_with::<TTo, From>(self, Some(registry))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<'a, From: CastSource + ?Sized> CrossCast for Pin<&'a mut From> {
+    type Output<TTo: CastTarget + ?Sized> = Pin<&'a mut TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Pin<&'a mut TTo>, CastErrorWith<Self>> {
+        cast_pin_mut_with::<TTo, From>(self, None)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Pin<&'a mut TTo>, CastErrorWith<Self>> {
+        cast_pin_mut_with::<TTo, From>(self, Some(registry))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<'b, From: CastSource + ?Sized> CrossCast for Ref<'b, From> {
+    type Output<TTo: CastTarget + ?Sized> = Ref<'b, TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<Ref<'b, TTo>, CastErrorWith<Self>> {
+        cast_cell_ref_with::<TTo, From>(self, None)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<Ref<'b, TTo>, CastErrorWith<Self>> {
+        cast_cell_ref_with::<TTo, From>(self, Some(registry))
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<'b, From: CastSource + ?Sized> CrossCast for RefMut<'b, From> {
+    type Output<TTo: CastTarget + ?Sized> = RefMut<'b, TTo>;
+
+    #[inline]
+    fn cast<TTo: CastTarget + ?Sized>(self) -> Result<RefMut<'b, TTo>, CastErrorWith<Self>> {
+        cast_cell_ref_mut_with::<TTo, From>(self, None)
+    }
+
+    #[inline]
+    fn cast_in<TTo: CastTarget + ?Sized>(self, registry: &Registry) -> Result<RefMut<'b, TTo>, CastErrorWith<Self>> {
+        cast_cell_ref_mut_with::<TTo, From>(self, Some(registry))
+    }
+}
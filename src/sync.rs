@@ -0,0 +1,189 @@
+// What the registry needs from `std`, with `core`/`alloc` stand-ins when the `std` feature is off
+
+/// The registry's keys are all `TypeId`s, or tuples of them
+#[cfg(feature = "std")]
+pub(crate) type Map<K, V> = std::collections::HashMap<K, V, core::hash::BuildHasherDefault<TypeIdHasher>>;
+
+/// `TypeId`s are already hashes, so there is no need to hash them again with SipHash: each one
+/// is mixed in with a rotation, which keeps `(a, b)` and `(b, a)` apart.
+#[derive(Default)]
+pub(crate) struct TypeIdHasher(u64);
+
+impl core::hash::Hasher for TypeIdHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = self.0.rotate_left(32) ^ n;
+    }
+
+    // `TypeId` only writes a `u64`, this is for anything else
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+}
+
+/// `TypeId` is `Ord`, so without hashing from `std` the registry is kept in a `BTreeMap`
+#[cfg(not(feature = "std"))]
+pub(crate) type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+
+/// Runs `f`, timing it if there is a clock
+#[cfg(feature = "std")]
+pub(crate) fn timed<T>(f: impl FnOnce() -> T) -> (T, Option<core::time::Duration>) {
+    let started = std::time::Instant::now();
+    let result = f();
+    (result, Some(started.elapsed()))
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn timed<T>(f: impl FnOnce() -> T) -> (T, Option<core::time::Duration>) {
+    (f(), None)
+}
+
+/// `LazyLock`, which can also tell whether it is initialized without initializing it
+#[cfg(feature = "std")]
+pub(crate) struct Lazy<T> {
+    value: std::sync::OnceLock<T>,
+    init: fn() -> T,
+}
+
+#[cfg(feature = "std")]
+impl<T> Lazy<T> {
+    pub(crate) const fn new(init: fn() -> T) -> Self {
+        Self { value: std::sync::OnceLock::new(), init }
+    }
+
+    /// The value, if it has been initialized
+    pub(crate) fn get(&self) -> Option<&T> {
+        self.value.get()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> core::ops::Deref for Lazy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.get_or_init(self.init)
+    }
+}
+#[cfg(not(feature = "std"))]
+pub(crate) use spin::Lazy;
+
+#[cfg(feature = "std")]
+pub(crate) struct WriterLock(std::sync::Mutex<()>);
+
+#[cfg(feature = "std")]
+impl WriterLock {
+    pub(crate) const fn new() -> Self {
+        Self(std::sync::Mutex::new(()))
+    }
+
+    pub(crate) fn lock(&self) -> impl Drop + '_ {
+        self.0.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) use spin::WriterLock;
+
+#[cfg(not(feature = "std"))]
+mod spin {
+    use core::cell::UnsafeCell;
+    use core::hint::spin_loop;
+    use core::mem::MaybeUninit;
+    use core::ops::Deref;
+    use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+    const UNINIT: u8 = 0;
+    const RUNNING: u8 = 1;
+    const DONE: u8 = 2;
+    const POISONED: u8 = 3;
+
+    /// `LazyLock` without `std`: the first thread to get here runs `init` while the others spin
+    pub(crate) struct Lazy<T> {
+        state: AtomicU8,
+        value: UnsafeCell<MaybeUninit<T>>,
+        init: fn() -> T,
+    }
+
+    // SAFETY: the value is written once, before `DONE` is published, and only read after it
+    unsafe impl<T: Send + Sync> Sync for Lazy<T> {}
+
+    impl<T> Lazy<T> {
+        pub(crate) const fn new(init: fn() -> T) -> Self {
+            Self { state: AtomicU8::new(UNINIT), value: UnsafeCell::new(MaybeUninit::uninit()), init }
+        }
+
+        fn force(&self) -> &T {
+            loop {
+                match self.state.compare_exchange_weak(UNINIT, RUNNING, Ordering::Acquire, Ordering::Acquire) {
+                    Ok(_) => {
+                        // Marks the value as poisoned if `init` panics, instead of spinning forever
+                        struct Poison<'a>(&'a AtomicU8);
+                        impl Drop for Poison<'_> {
+                            fn drop(&mut self) {
+                                self.0.store(POISONED, Ordering::Release);
+                            }
+                        }
+                        let poison = Poison(&self.state);
+                        let value = (self.init)();
+                        // SAFETY: only the thread that moved the state to `RUNNING` writes
+                        unsafe { (*self.value.get()).write(value) };
+                        core::mem::forget(poison);
+                        self.state.store(DONE, Ordering::Release);
+                        break;
+                    }
+                    Err(DONE) => break,
+                    Err(POISONED) => panic!("the initialization of a lazy value panicked"),
+                    Err(_) => spin_loop(),
+                }
+            }
+            // SAFETY: the state is `DONE`, so the value was written
+            unsafe { (*self.value.get()).assume_init_ref() }
+        }
+
+        /// The value, if it has been initialized
+        pub(crate) fn get(&self) -> Option<&T> {
+            // SAFETY: the state is `DONE`, so the value was written
+            (self.state.load(Ordering::Acquire) == DONE).then(|| unsafe { (*self.value.get()).assume_init_ref() })
+        }
+    }
+
+    impl<T> Deref for Lazy<T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            self.force()
+        }
+    }
+
+    /// A spin lock around nothing, only used to serialize writers
+    pub(crate) struct WriterLock(AtomicBool);
+
+    pub(crate) struct WriterGuard<'a>(&'a AtomicBool);
+
+    impl Drop for WriterGuard<'_> {
+        fn drop(&mut self) {
+            self.0.store(false, Ordering::Release);
+        }
+    }
+
+    impl WriterLock {
+        pub(crate) const fn new() -> Self {
+            Self(AtomicBool::new(false))
+        }
+
+        pub(crate) fn lock(&self) -> WriterGuard<'_> {
+            while self.0.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+                spin_loop();
+            }
+            WriterGuard(&self.0)
+        }
+    }
+}
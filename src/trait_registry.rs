@@ -1,17 +1,25 @@
-use std::any::{type_name, Any, TypeId};
-use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
-use std::fmt::{Debug, Formatter};
-use std::marker::{PhantomData, Unsize};
-use std::mem::transmute;
-use std::ptr::{metadata, null, DynMetadata, Pointee};
-use std::sync::LazyLock;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::{type_name, Any, TypeId};
+use core::error::Error;
+use core::fmt::{Debug, Display, Formatter};
+#[cfg(not(feature = "nightly"))]
+use core::mem::transmute;
+#[cfg(feature = "nightly")]
+use core::ptr::{DynMetadata, Pointee};
+use core::time::Duration;
+use crate::sync::{timed, Lazy, Map, WriterLock};
+use runtime::RuntimeRegistry;
+#[cfg(not(feature = "linkme"))]
 use inventory::collect;
 
 
 
 
 
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum CastError {
     TraitNotImplemented{
         trait_name: &'static str,
@@ -19,151 +27,1343 @@ pub enum CastError {
         type_name: &'static str,
         type_id: TypeId,
     },
+    /// The type is registered, but not for this trait
     CombinationNotRegistered {
         trait_name: &'static str,
         trait_id: TypeId,
         type_name: &'static str,
         type_id: TypeId,
+        /// Whether the lookup was in a [`Registry`] value rather than in the global registry
+        scoped: bool,
+        /// The names of the traits the type was registered for in the registry looked in when
+        /// the lookup failed, sorted. Shared with the registry, so making the error does not
+        /// allocate
+        registered_traits: Arc<[&'static str]>,
+    },
+    /// The type is not registered for any trait
+    TypeNotRegistered {
+        trait_name: &'static str,
+        trait_id: TypeId,
+        type_name: &'static str,
+        type_id: TypeId,
+        /// Whether the lookup was in a [`Registry`] value rather than in the global registry
+        scoped: bool,
+    },
+    /// The trait is a `Send`/`Sync` variant of a registered trait, which the type implements,
+    /// but the pointer being cast does not carry those auto traits in its type. Casts keep the
+    /// auto traits of the source, they never add any
+    AutoTraitsNotCarried {
+        trait_name: &'static str,
+        trait_id: TypeId,
+        type_name: &'static str,
+        type_id: TypeId,
     },
 
 
 }
 impl Debug for CastError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+impl Display for CastError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::TraitNotImplemented{trait_name, type_name,.. } => {
                 f.write_fmt(format_args!("trait '{trait_name}' not implemented by the underlying concrete type '{type_name}'"))
             },
-            Self::CombinationNotRegistered{trait_name, type_name,.. } => {
-                f.write_fmt(format_args!("trait '{trait_name}' has not been registered to check if it is implemented by the underlying concrete type '{type_name}'"))
+            Self::CombinationNotRegistered{trait_name, type_name, scoped, registered_traits, .. } => {
+                f.write_fmt(format_args!("trait '{trait_name}' has not been registered to check if it is implemented by the underlying concrete type '{type_name}'"))?;
+                if *scoped {
+                    f.write_str(" in the registry it was looked up in")?;
+                }
+                f.write_str(", which is registered for ")?;
+                for (index, registered) in registered_traits.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { ", " };
+                    f.write_fmt(format_args!("{separator}'{registered}'"))?;
+                }
+                f.write_fmt(format_args!(". {}", RegistrationSnippet { type_name, trait_name, scoped: *scoped }))
+            },
+            Self::TypeNotRegistered{trait_name, type_name, scoped, .. } => {
+                f.write_fmt(format_args!("the underlying concrete type '{type_name}' has not been registered for any trait, so it can not be checked for trait '{trait_name}'. {}", RegistrationSnippet { type_name, trait_name, scoped: *scoped }))
+            },
+            Self::AutoTraitsNotCarried{trait_name, type_name, .. } => {
+                f.write_fmt(format_args!("trait '{trait_name}' has auto traits that the pointer to the underlying concrete type '{type_name}' does not carry in its type, and casts do not add any. Cast from a trait object type that has them"))
             },
         }
 
     }
 }
+impl Error for CastError {}
+
+/// The code that registers a missing combination: a `register_types!` invocation for the global
+/// registry, or an insert into the registry the lookup was in
+struct RegistrationSnippet<'a> {
+    type_name: &'a str,
+    trait_name: &'a str,
+    scoped: bool,
+}
+impl Display for RegistrationSnippet<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let Self { type_name, trait_name, scoped } = self;
+        if *scoped {
+            f.write_fmt(format_args!("Register it with: registry.insert(vtable_entry!({type_name}, {trait_name}))"))
+        } else {
+            f.write_fmt(format_args!("Register it with: register_types!{{ implementors: [{type_name}], traits: [{trait_name}] }}"))
+        }
+    }
+}
+#[cfg(feature = "nightly")]
 #[derive(Clone,Copy)]
+#[allow(dead_code)]
 pub struct VTable(&'static ());
+
+#[cfg(feature = "nightly")]
+impl VTable {
+    /// Makes a pointer to `TTo` out of a pointer to the implementor of the entry the vtable is in
+    ///
+    /// # Safety
+    /// `TTo` must be the trait object type this vtable was made for.
+    pub(crate) unsafe fn attach<TTo: CastTarget + ?Sized>(self, data: *const ()) -> *const TTo {
+        core::ptr::from_raw_parts(data, unsafe { crate::handy_functions::generic_transmute::<VTable, DynMetadata<TTo>>(self) })
+    }
+}
+
+/// Without `DynMetadata`, the vtable is kept as a function turning a data pointer into a
+/// `*const dyn Trait`, with the trait erased.
+#[cfg(not(feature = "nightly"))]
+#[derive(Clone,Copy)]
+pub struct VTable(fn());
+
+#[cfg(not(feature = "nightly"))]
+impl VTable {
+    /// # Safety
+    /// `caster` must turn pointers to the implementor of the entry the vtable goes in into
+    /// pointers to the same value as `TTo`, the trait object type of the entry. Casts call it on
+    /// such pointers without checking.
+    #[doc(hidden)]
+    pub unsafe fn from_caster<TTo: ?Sized>(caster: fn(*const ()) -> *const TTo) -> Self {
+        unsafe { VTable(transmute::<fn(*const ()) -> *const TTo, fn()>(caster)) }
+    }
+
+    /// Makes a pointer to `TTo` out of a pointer to the implementor of the entry the vtable is in
+    ///
+    /// # Safety
+    /// `TTo` must be the trait object type this vtable was made for.
+    pub(crate) unsafe fn attach<TTo: CastTarget + ?Sized>(self, data: *const ()) -> *const TTo {
+        unsafe { transmute::<fn(), fn(*const ()) -> *const TTo>(self.0)(data) }
+    }
+}
+
+/// A trait object type that can be cast to
+#[cfg(feature = "nightly")]
+pub trait CastTarget: Pointee<Metadata=DynMetadata<Self>> + 'static {}
+#[cfg(feature = "nightly")]
+impl<T: ?Sized + Pointee<Metadata=DynMetadata<T>> + 'static> CastTarget for T {}
+
+/// A trait object type that can be cast to. Stable Rust can not tell trait objects apart, but
+/// only trait object types ever get registered, so anything else is just never found.
+#[cfg(not(feature = "nightly"))]
+pub trait CastTarget: 'static {}
+#[cfg(not(feature = "nightly"))]
+impl<T: ?Sized + 'static> CastTarget for T {}
+
+/// The `Send`/`Sync` variants of a trait object type, in this order: `dyn Trait + Send`,
+/// `dyn Trait + Sync` and `dyn Trait + Send + Sync`
+const AUTO_VARIANTS: usize = 3;
+
+/// The auto traits the trait object type of a pointer being cast has, which are the only ones a
+/// cast may keep: it can reach a `Send`/`Sync` variant of a registered trait only if they include
+/// the variant's auto traits. Looking a type up rather than a pointer uses `SEND_SYNC`, the type
+/// has whichever it implements
+#[doc(hidden)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AutoTraits(u8);
+
+impl AutoTraits {
+    pub const NONE: Self = Self(0);
+    pub const SEND: Self = Self(1);
+    pub const SYNC: Self = Self(2);
+    pub const SEND_SYNC: Self = Self(3);
+
+    /// The auto traits of the trait object type `T`
+    #[cfg(feature = "nightly")]
+    pub(crate) const fn of<T: ?Sized>() -> Self {
+        Self(specialization::is_send::<T>() as u8 | (specialization::is_sync::<T>() as u8) << 1)
+    }
+
+    /// Whether these include the auto traits of the variant at index `variant`. The variants are
+    /// in the order of their bits: `Send` is 1, `Sync` 2 and `Send + Sync` 3
+    fn cover(self, variant: usize) -> bool {
+        (variant as u8 + 1) & !self.0 == 0
+    }
+}
+
+#[derive(Clone, Copy)]
 pub struct VTableMapInstance{
-    implementor_type_id: ImplementorTypeId,
+    implementor: ImplementorInfo,
     trait_type_id: TraitTypeId,
-    v_table: Option<VTable>
+    trait_name: &'static str,
+    v_table: Option<VTable>,
+    site: RegistrationSite,
+    // The variants of the trait, when the registration could name them, and the vtables of the
+    // implementor for them, when known
+    variant_ids: Option<[TraitTypeId; AUTO_VARIANTS]>,
+    variant_v_tables: Option<[Option<VTable>; AUTO_VARIANTS]>,
 }
 
 impl VTableMapInstance {
-    pub const fn new(    implementor_type_id: ImplementorTypeId,
+    /// An entry registering the implementor for the trait object type `trait_type_id`. The
+    /// registration macros, such as [`vtable_entry!`](crate::vtable_entry), make them safely.
+    ///
+    /// # Safety
+    /// `v_table` must be `None`, or the vtable of the implementor's type for the trait object
+    /// type identified by `trait_type_id`. Casts attach it to pointers to the implementor
+    /// without checking, so any other vtable reads out of bounds or calls the wrong functions.
+    pub const unsafe fn new(    implementor: ImplementorInfo,
     trait_type_id: TraitTypeId,
-    v_table: Option<VTable>) -> Self{
-        Self{implementor_type_id, trait_type_id, v_table}
+    trait_name: &'static str,
+    v_table: Option<VTable>,
+    site: RegistrationSite) -> Self{
+        Self{implementor, trait_type_id, trait_name, v_table, site, variant_ids: None, variant_v_tables: None}
+    }
+
+    /// Adds the `Send`/`Sync` variants of the trait and the implementor's vtables for them, for
+    /// the registration macros. Auto traits change the `TypeId` of a trait object type, so this is
+    /// how a single entry answers for `dyn Trait + Send + Sync` too.
+    ///
+    /// # Safety
+    /// Each of `variant_v_tables` must be `None`, or the vtable of the implementor's type for the
+    /// trait object type of the same index in `variant_ids`, see [`VTableMapInstance::new`].
+    #[doc(hidden)]
+    pub const unsafe fn with_auto_variants(self, variant_ids: [TraitTypeId; AUTO_VARIANTS], variant_v_tables: [Option<VTable>; AUTO_VARIANTS]) -> Self {
+        Self { variant_ids: Some(variant_ids), variant_v_tables: Some(variant_v_tables), ..self }
+    }
+    pub fn implementor(&self) -> ImplementorInfo {
+        self.implementor
+    }
+    pub fn trait_info(&self) -> TraitInfo {
+        TraitInfo { trait_id: self.trait_type_id, trait_name: self.trait_name }
+    }
+    /// Whether the implementor implements the trait
+    pub fn implemented(&self) -> bool {
+        self.v_table.is_some()
+    }
+    pub fn site(&self) -> RegistrationSite {
+        self.site
+    }
+}
+
+/// Where a registration macro was invoked
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RegistrationSite {
+    pub module_path: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+}
+
+impl RegistrationSite {
+    pub const fn new(module_path: &'static str, file: &'static str, line: u32) -> Self {
+        Self { module_path, file, line }
+    }
+
+    /// The crate the registration was made in, the first segment of the module path
+    pub fn crate_name(&self) -> &'static str {
+        self.module_path.split("::").next().unwrap_or(self.module_path)
     }
 }
-collect!(VTableMapInstance);
+
+/// A registered concrete type
+#[derive(Clone, Copy, Debug)]
+pub struct ImplementorInfo {
+    pub type_id: ImplementorTypeId,
+    pub type_name: &'static str,
+    pub size: usize,
+    pub align: usize,
+    constructor: Option<fn() -> Box<dyn Castable>>,
+}
+
+impl ImplementorInfo {
+    #[cfg(feature = "nightly")]
+    pub const fn of<Type: 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<Type>(),
+            type_name: const_type_name::<Type>(),
+            size: size_of::<Type>(),
+            align: align_of::<Type>(),
+            constructor: generate_default_constructor::<Type>(),
+        }
+    }
+
+    /// Without specialization `Default` can not be detected here, the registration macros find
+    /// the constructor and add it with [`ImplementorInfo::with_constructor`].
+    #[cfg(not(feature = "nightly"))]
+    pub fn of<Type: 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<Type>(),
+            type_name: type_name::<Type>(),
+            size: size_of::<Type>(),
+            align: align_of::<Type>(),
+            constructor: None,
+        }
+    }
+
+    #[cfg(not(feature = "nightly"))]
+    #[doc(hidden)]
+    pub fn with_constructor(self, constructor: Option<fn() -> Box<dyn Castable>>) -> Self {
+        Self { constructor, ..self }
+    }
+
+    /// Builds a new value of this type through its `Default` implementation.
+    ///
+    /// Returns `None` if the type does not implement `Default`.
+    pub fn construct(&self) -> Option<Box<dyn Castable>> {
+        self.constructor.map(|constructor| constructor())
+    }
+}
+
+impl PartialEq for ImplementorInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_id == other.type_id
+    }
+}
+impl Eq for ImplementorInfo {}
+
+/// `type_name` usable in the constant the registration macros submit
+#[cfg(feature = "nightly")]
+pub const fn const_type_name<T: ?Sized>() -> &'static str {
+    type_name::<T>()
+}
+/// Same as `type_name`, stable Rust can not call it in constants
+#[cfg(not(feature = "nightly"))]
+pub fn const_type_name<T: ?Sized>() -> &'static str {
+    type_name::<T>()
+}
+#[cfg(feature = "nightly")]
+mod specialization;
+#[cfg(feature = "inline-cache")]
+mod inline_cache;
+mod runtime;
+#[cfg(feature = "nightly")]
+pub use specialization::{generate_default_constructor, generate_trait_vtable};
+
+/// What the registration macros submit on stable: the entry itself can not be built in a constant,
+/// so it is built when the registry is
+#[cfg(not(feature = "nightly"))]
+pub struct DeferredEntry(fn() -> VTableMapInstance);
+#[cfg(not(feature = "nightly"))]
+impl DeferredEntry {
+    pub const fn new(entry: fn() -> VTableMapInstance) -> Self {
+        Self(entry)
+    }
+}
+
+/// What the registration macros submit
+#[cfg(feature = "nightly")]
+#[doc(hidden)]
+pub type SubmittedEntry = VTableMapInstance;
+#[cfg(not(feature = "nightly"))]
+#[doc(hidden)]
+pub type SubmittedEntry = DeferredEntry;
+
+#[cfg(not(feature = "linkme"))]
+collect!(SubmittedEntry);
+
+/// With the `linkme` feature, the registration macros add their entries to this slice at link
+/// time instead of submitting them to `inventory`, so no code runs before `main`
+#[cfg(feature = "linkme")]
+#[doc(hidden)]
+#[linkme::distributed_slice]
+pub static SUBMITTED_ENTRIES: [SubmittedEntry];
+
+/// Every entry submitted with the registration macros
+fn submitted_entries() -> impl Iterator<Item = VTableMapInstance> {
+    #[cfg(not(feature = "linkme"))]
+    let submitted = inventory::iter::<SubmittedEntry>.into_iter();
+    #[cfg(feature = "linkme")]
+    let submitted = SUBMITTED_ENTRIES.iter();
+    submitted.map(build_entry)
+}
+#[cfg(feature = "nightly")]
+fn build_entry(submitted: &SubmittedEntry) -> VTableMapInstance {
+    *submitted
+}
+#[cfg(not(feature = "nightly"))]
+fn build_entry(submitted: &SubmittedEntry) -> VTableMapInstance {
+    (submitted.0)()
+}
+
+/// Finds the `Default` constructor of a type on stable Rust, through autoref specialization
+#[cfg(not(feature = "nightly"))]
+#[doc(hidden)]
+pub mod __private {
+    use alloc::boxed::Box;
+    use core::marker::PhantomData;
+    use super::Castable;
+
+    pub struct DefaultProbe<T>(PhantomData<T>);
+    #[allow(clippy::new_without_default)]
+    impl<T> DefaultProbe<T> {
+        pub const fn new() -> Self {
+            Self(PhantomData)
+        }
+    }
+    pub trait HasDefault {
+        fn constructor(&self) -> Option<fn() -> Box<dyn Castable>>;
+    }
+    impl<T: Default + 'static> HasDefault for DefaultProbe<T> {
+        fn constructor(&self) -> Option<fn() -> Box<dyn Castable>> {
+            fn construct<T: Default + 'static>() -> Box<dyn Castable> {
+                Box::new(T::default())
+            }
+            Some(construct::<T>)
+        }
+    }
+    pub trait NoDefault {
+        fn constructor(&self) -> Option<fn() -> Box<dyn Castable>> {
+            None
+        }
+    }
+    impl<T> NoDefault for &DefaultProbe<T> {}
+}
 pub trait Castable: Any{
     fn type_name(&self) -> &'static str;
+    #[cfg(feature = "nightly")]
+    /// Same as [`Any::type_id`], but only reads the vtable, so it can be called through pointers
+    /// whose pointee has been dropped or never existed (e.g. a `Weak` with no strong references).
+    fn type_id_of_ptr(self: *const Self) -> TypeId;
+    #[cfg(feature = "nightly")]
+    /// Same as [`Castable::type_name`], but only reads the vtable.
+    fn type_name_of_ptr(self: *const Self) -> &'static str;
 }
 impl<T: Any> Castable for T{
     fn type_name(&self) -> &'static str {
         type_name::<Self>()
     }
+    #[cfg(feature = "nightly")]
+    fn type_id_of_ptr(self: *const Self) -> TypeId {
+        TypeId::of::<Self>()
+    }
+    #[cfg(feature = "nightly")]
+    fn type_name_of_ptr(self: *const Self) -> &'static str {
+        type_name::<Self>()
+    }
 }
 type  ImplementorTypeId = TypeId;
 type  TraitTypeId = TypeId;
 
-    static VTABLE_REGISTRY: LazyLock<HashMap<ImplementorTypeId, HashMap<TraitTypeId,Option<VTable>>>> = LazyLock::new(||{
-        let mut za_hash = HashMap::new();
-        for i in inventory::iter::<VTableMapInstance> {
-            za_hash.insert(i.implementor_type_id, HashMap::new());
+/// What the registry knows about one (implementor, trait) combination
+#[derive(Clone)]
+struct TraitEntry {
+    trait_name: &'static str,
+    v_table: Option<VTable>,
+    // For the `Send`/`Sync` variants of the trait, `None` if not known
+    variant_v_tables: Option<[Option<VTable>; AUTO_VARIANTS]>,
+    // The combination itself and the variants of the trait, to check that a cached entry is for
+    // the combination looked up
+    #[cfg(feature = "inline-cache")]
+    key: (ImplementorTypeId, TraitTypeId),
+    #[cfg(feature = "inline-cache")]
+    variant_ids: Option<[TraitTypeId; AUTO_VARIANTS]>,
+}
+
+impl TraitEntry {
+    fn new(entry: &VTableMapInstance) -> Self {
+        Self {
+            trait_name: entry.trait_name,
+            v_table: entry.v_table,
+            variant_v_tables: entry.variant_v_tables,
+            #[cfg(feature = "inline-cache")]
+            key: (entry.implementor.type_id, entry.trait_type_id),
+            #[cfg(feature = "inline-cache")]
+            variant_ids: entry.variant_ids,
         }
-        for i in inventory::iter::<VTableMapInstance> {
-            let gotten = za_hash.get_mut(&i.implementor_type_id).unwrap();
-            gotten.insert(i.trait_type_id,i.v_table);
+    }
+}
+
+/// A lookup that found a registered combination: its entry, and the vtable for the trait looked
+/// up, which is either the entry's trait or one of its `Send`/`Sync` variants
+type Found<'a> = (&'a TraitEntry, Option<VTable>);
+
+/// Looks `trait_id` up as the variant of a registered trait. `rows` are the registered traits it
+/// is a variant of, with which variant it is, and only variants `carried` covers are found
+fn find_variant<'a, 'r>(entry: impl Fn(TraitTypeId) -> Option<&'a TraitEntry>, rows: impl IntoIterator<Item = &'r (TraitTypeId, usize)>, carried: AutoTraits) -> Option<Found<'a>> {
+    rows.into_iter().filter(|(_, variant)| carried.cover(*variant)).find_map(|&(principal, variant)| {
+        let found = entry(principal)?;
+        Some((found, found.variant_v_tables?[variant]))
+    })
+}
+
+/// The rows pointing the `Send`/`Sync` variants of the entry's trait back to it, by variant
+fn variant_rows(entry: &VTableMapInstance) -> impl Iterator<Item = (TraitTypeId, (TraitTypeId, usize))> {
+    let trait_id = entry.trait_type_id;
+    entry.variant_ids.into_iter().flatten().enumerate()
+        // `dyn Trait + Send` is its own `Send` variant
+        .filter(move |(_, variant_id)| *variant_id != trait_id)
+        .map(move |(variant, variant_id)| (variant_id, (trait_id, variant)))
+}
+
+/// Adds the rows pointing the `Send`/`Sync` variants of the entry's trait back to it
+fn add_variant_rows(principals: &mut Map<TraitTypeId, Vec<(TraitTypeId, usize)>>, entry: &VTableMapInstance) {
+    for (variant_id, row) in variant_rows(entry) {
+        let rows = principals.entry(variant_id).or_default();
+        if !rows.contains(&row) {
+            rows.push(row);
         }
-        za_hash
-    });
+    }
+}
 
+/// The implementors among `candidates` that `find` says implement the trait, sorted by name
+fn implementing<'a>(candidates: impl Iterator<Item = &'a ImplementorInfo>, find: impl Fn(ImplementorTypeId) -> Option<Found<'a>>) -> Vec<ImplementorInfo> {
+    let mut implementors: Vec<ImplementorInfo> = candidates
+        .filter(|info| find(info.type_id).is_some_and(|(_, v_table)| v_table.is_some()))
+        .copied()
+        .collect();
+    implementors.sort_by_key(|info| info.type_name);
+    implementors.dedup();
+    implementors
+}
 
+/// The same (implementor, trait) combination registered more than once
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateRegistration {
+    pub implementor: ImplementorInfo,
+    pub trait_info: TraitInfo,
+    /// Every registration of the combination, with whether it said the trait is implemented
+    pub registrations: Vec<(RegistrationSite, bool)>,
+}
 
-/// Gets the vtable
-pub(crate) fn get_vtable<TCastTo: ?Sized + 'static + Pointee<Metadata=DynMetadata<TCastTo>>>(obj: &(impl Castable + ?Sized)) -> Result<VTable, CastError>{
-    let obj_type_id = obj.type_id();
-    let type_registration_maybe = VTABLE_REGISTRY.get(&obj_type_id);
+impl DuplicateRegistration {
+    /// Whether every registration gave the same implemented / not implemented answer
+    pub fn answers_agree(&self) -> bool {
+        self.registrations.windows(2).all(|pair| pair[0].1 == pair[1].1)
+    }
+}
 
-    match type_registration_maybe{
-        Some(type_registration) => {
-            match type_registration.get(&TypeId::of::<TCastTo>()) {
-                None => {
-                    Err(CastError::CombinationNotRegistered{trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj.type_name(), type_id: obj_type_id })
-                }
-                Some(gotten) => {
-                    match gotten {
-                        None => {
-                            Err(CastError::TraitNotImplemented {trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj.type_name(), type_id: obj_type_id })
-                        } Some(found) => {
-                            Ok(*found)
-                        }
-                    }
-                }
+/// Problems found while building the registry
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistryDiagnostics {
+    pub duplicates: Vec<DuplicateRegistration>,
+}
+
+impl RegistryDiagnostics {
+    /// Whether any duplicate registration disagrees with another one about the same combination
+    pub fn has_conflicts(&self) -> bool {
+        self.duplicates.iter().any(|duplicate| !duplicate.answers_agree())
+    }
+}
+
+/// A set of (implementor, trait) registrations to cast with.
+///
+/// The free functions of this module and the `trait_cross_cast_*` functions use the global
+/// registry, made of every `register_types!` invocation plus [`register`]. A `Registry` value is
+/// independent of it: casts through the `_in` functions and [`CrossCast::cast_in`] only see the
+/// registrations of the registry they are given.
+///
+/// [`CrossCast::cast_in`]: crate::cast_fns::CrossCast::cast_in
+#[derive(Clone, Default)]
+pub struct Registry {
+    // Flat, so a cast is a single lookup
+    vtables: Map<(ImplementorTypeId, TraitTypeId), TraitEntry>,
+    // The registered traits of each implementor, for `registered_traits_of`
+    traits_of: Map<ImplementorTypeId, Vec<TraitTypeId>>,
+    // Reverse of vtables: for each trait, the registered types that implement it, sorted by name
+    implementors: Map<TraitTypeId, Vec<ImplementorInfo>>,
+    // For each `Send`/`Sync` variant of a registered trait, the trait and which variant it is.
+    // The variants have no entries of their own, they are looked up in the entries of the trait
+    principals: Map<TraitTypeId, Vec<(TraitTypeId, usize)>>,
+    // The names of the registered traits of each implementor, sorted, for the errors of lookups
+    // of other traits
+    trait_names_of: Map<ImplementorTypeId, Arc<[&'static str]>>,
+    diagnostics: RegistryDiagnostics,
+    build_time: Option<Duration>,
+}
+
+impl Registry {
+    /// An empty registry, to fill with [`Registry::insert`] or [`Registry::register`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every registration made with `register_types!` and the attribute macros.
+    ///
+    /// Registrations made at runtime with [`register_entry`] are not included.
+    pub fn from_inventory() -> Self {
+        Self::from_entries(submitted_entries())
+    }
+
+    /// The registrations made with `register_types!` and the attribute macros that `filter`
+    /// accepts
+    pub fn from_inventory_filtered(mut filter: impl FnMut(&VTableMapInstance) -> bool) -> Self {
+        Self::from_entries(submitted_entries().filter(|entry| filter(entry)))
+    }
+
+    /// A registry made of `entries`. Duplicates are handled like in the global registry, see
+    /// [`diagnostics`].
+    ///
+    /// Entries can only be made by the registration macros, or by [`VTableMapInstance::new`]
+    /// whose caller vouches for the vtable, so they are trusted as they are.
+    pub fn from_entries(entries: impl IntoIterator<Item = VTableMapInstance>) -> Self {
+        build_vtable_registry(entries, cfg!(feature = "strict-registration"))
+    }
+
+    /// Adds `entry`, usually made with [`vtable_entry!`](crate::vtable_entry). Inserting a
+    /// combination that is already registered does nothing. Like in [`Registry::from_entries`],
+    /// the entry is trusted as it is.
+    pub fn insert(&mut self, entry: VTableMapInstance) {
+        if self.entry(entry.implementor.type_id, entry.trait_type_id).is_some() {
+            return;
+        }
+        self.vtables.insert((entry.implementor.type_id, entry.trait_type_id), TraitEntry::new(&entry));
+        add_variant_rows(&mut self.principals, &entry);
+        self.traits_of.entry(entry.implementor.type_id).or_default().push(entry.trait_type_id);
+        let trait_names = self.trait_names(entry.implementor.type_id);
+        self.trait_names_of.insert(entry.implementor.type_id, trait_names);
+        if entry.v_table.is_some() {
+            let implementors = self.implementors.entry(entry.trait_type_id).or_default();
+            implementors.push(entry.implementor);
+            implementors.sort_by_key(|info| info.type_name);
+        }
+    }
+
+    /// Registers `Type` for the trait object type `Trait`. Registering a combination that is
+    /// already registered does nothing.
+    ///
+    /// `Trait` with `Send` and `Sync` added is covered too, as far as `Type` implements them, once
+    /// those trait object types are known to this registry: that is when `Trait` is registered
+    /// with a macro here, before or after. Generic code can not name `Trait + Send` on its own,
+    /// and the global registry is not consulted.
+    #[cfg(feature = "nightly")]
+    #[track_caller]
+    pub fn register<Type: 'static, Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>> + 'static>(&mut self) {
+        let entry = generic_entry::<Type, Trait>();
+        let variant_ids = self.variant_ids_of(entry.trait_type_id);
+        self.insert(VTableMapInstance { variant_ids, ..entry });
+    }
+
+    /// The `Send`/`Sync` variants of `trait_id`, by variant, if a macro registered `trait_id` here
+    #[cfg(feature = "nightly")]
+    fn variant_ids_of(&self, trait_id: TraitTypeId) -> Option<[TraitTypeId; AUTO_VARIANTS]> {
+        let mut variant_ids = [None; AUTO_VARIANTS];
+        for (variant_id, rows) in &self.principals {
+            for &(_, variant) in rows.iter().filter(|(principal, _)| *principal == trait_id) {
+                variant_ids[variant] = Some(*variant_id);
             }
         }
-        None => {
-            Err(CastError::CombinationNotRegistered {trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj.type_name(), type_id: obj_type_id })
+        if variant_ids.iter().all(Option::is_none) {
+            return None;
+        }
+        // The variants without rows are the trait itself, like the `Send` variant of `dyn Trait + Send`
+        Some(variant_ids.map(|id| id.unwrap_or(trait_id)))
+    }
+
+    /// Duplicate registrations found while building this registry
+    pub fn diagnostics(&self) -> &RegistryDiagnostics {
+        &self.diagnostics
+    }
+
+    /// The size of this registry, and how long building it from entries took
+    pub fn stats(&self) -> RegistryStats {
+        let traits_of = self.traits_of.values()
+            .map(|traits| size_of::<(ImplementorTypeId, Vec<TraitTypeId>)>() + traits.len() * size_of::<TraitTypeId>());
+        let implementors = self.implementors.values()
+            .map(|implementors| size_of::<(TraitTypeId, Vec<ImplementorInfo>)>() + implementors.len() * size_of::<ImplementorInfo>());
+        let principals = self.principals.values()
+            .map(|rows| size_of::<(TraitTypeId, Vec<(TraitTypeId, usize)>)>() + rows.len() * size_of::<(TraitTypeId, usize)>());
+        RegistryStats {
+            entries: self.vtables.len(),
+            implementors: self.traits_of.len(),
+            build_time: self.build_time,
+            memory: self.vtables.len() * size_of::<((ImplementorTypeId, TraitTypeId), TraitEntry)>()
+                + traits_of.chain(implementors).chain(principals).sum::<usize>(),
+        }
+    }
+
+    /// [`implements`] for this registry
+    pub fn implements<TTo: CastTarget + ?Sized>(&self, obj: &dyn Castable) -> Result<bool, CastError> {
+        vtable_found(get_vtable::<TTo>(obj, AutoTraits::SEND_SYNC, Some(self)))
+    }
+
+    /// [`type_implements`] for this registry
+    pub fn type_implements<Type: 'static, TTo: CastTarget + ?Sized>(&self) -> Result<bool, CastError> {
+        vtable_found(get_vtable_in::<TTo>(TypeId::of::<Type>(), type_name::<Type>(), AutoTraits::SEND_SYNC, Some(self)))
+    }
+
+    /// [`registered_traits_of_type_id`] for this registry
+    pub fn registered_traits_of_type_id(&self, type_id: TypeId) -> Option<RegisteredTraits> {
+        self.registered_entries(type_id).map(collect_registered_traits)
+    }
+
+    /// [`registered_traits_of`] for this registry
+    pub fn registered_traits_of(&self, obj: &dyn Castable) -> Option<RegisteredTraits> {
+        self.registered_traits_of_type_id(obj.type_id())
+    }
+
+    /// [`implementors_of`] for this registry
+    pub fn implementors_of<TTo: CastTarget + ?Sized>(&self) -> Vec<ImplementorInfo> {
+        let trait_id = TypeId::of::<TTo>();
+        let principals = self.principals_of(trait_id).map(|(principal, _)| *principal);
+        let candidates = core::iter::once(trait_id).chain(principals).filter_map(|trait_id| self.implementors.get(&trait_id)).flatten();
+        implementing(candidates, |type_id| self.find(type_id, trait_id, AutoTraits::SEND_SYNC))
+    }
+
+    fn entry(&self, obj_type_id: ImplementorTypeId, trait_id: TraitTypeId) -> Option<&TraitEntry> {
+        self.vtables.get(&(obj_type_id, trait_id))
+    }
+
+    /// The entry of the combination, or of the trait `trait_id` is a `Send`/`Sync` variant of, if
+    /// `carried` covers the variant
+    fn find(&self, obj_type_id: ImplementorTypeId, trait_id: TraitTypeId, carried: AutoTraits) -> Option<Found<'_>> {
+        let entry = |trait_id| self.entry(obj_type_id, trait_id);
+        if let Some(found) = entry(trait_id) {
+            return Some((found, found.v_table));
         }
+        find_variant(entry, self.principals_of(trait_id), carried)
+    }
+
+    /// The registered traits `trait_id` is a `Send`/`Sync` variant of
+    fn principals_of(&self, trait_id: TraitTypeId) -> impl Iterator<Item = &(TraitTypeId, usize)> {
+        self.principals.get(&trait_id).into_iter().flatten()
+    }
+
+    /// The names of the registered traits of the type, sorted, to keep in `trait_names_of`
+    fn trait_names(&self, type_id: ImplementorTypeId) -> Arc<[&'static str]> {
+        sorted_names(self.registered_entries(type_id).into_iter().flatten().map(|(_, entry)| entry.trait_name))
+    }
+
+    /// The names of the registered traits of the type, sorted, or `None` if it is not registered
+    /// at all
+    fn registered_trait_names(&self, type_id: ImplementorTypeId) -> Option<&Arc<[&'static str]>> {
+        self.trait_names_of.get(&type_id)
     }
 
+    /// The registered combinations of the type, or `None` if it is not registered at all
+    fn registered_entries(&self, type_id: ImplementorTypeId) -> Option<impl Iterator<Item = (&TraitTypeId, &TraitEntry)>> {
+        let traits = self.traits_of.get(&type_id)?;
+        Some(traits.iter().map(move |trait_id| (trait_id, &self.vtables[&(type_id, *trait_id)])))
+    }
+}
+
+/// Builds the registry out of `entries`. Later registrations of a combination overwrite earlier
+/// ones, and every repeat is recorded in the diagnostics. With `strict`, the first repeat whose
+/// answers disagree panics; repeats that agree are only recorded.
+pub(crate) fn build_vtable_registry(entries: impl IntoIterator<Item = VTableMapInstance>, strict: bool) -> Registry {
+    let (mut registry, build_time) = timed(|| build_vtable_tables(entries, strict));
+    registry.build_time = build_time;
+    registry
 }
-pub const fn generate_trait_vtable<Type: 'static,Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>> + 'static>() -> Option<VTable> {
-    struct AsDyn<Type: 'static> {
-        kk: PhantomData<fn() -> Type>,
+
+fn build_vtable_tables(entries: impl IntoIterator<Item = VTableMapInstance>, strict: bool) -> Registry {
+    let mut za_hash: Map<(ImplementorTypeId, TraitTypeId), (TraitEntry, RegistrationSite)> = Map::default();
+    let mut traits_of: Map<ImplementorTypeId, Vec<TraitTypeId>> = Map::default();
+    let mut implementors: Map<TraitTypeId, Vec<ImplementorInfo>> = Map::default();
+    let mut principals: Map<TraitTypeId, Vec<(TraitTypeId, usize)>> = Map::default();
+    let mut duplicates: Vec<DuplicateRegistration> = Vec::new();
+    let mut duplicate_indices: Map<(ImplementorTypeId, TraitTypeId), usize> = Map::default();
+    for i in entries {
+        if i.v_table.is_some() {
+            let trait_implementors = implementors.entry(i.trait_type_id).or_default();
+            if !trait_implementors.contains(&i.implementor) {
+                trait_implementors.push(i.implementor);
+            }
+        }
+        add_variant_rows(&mut principals, &i);
+        let replaced = za_hash.insert((i.implementor.type_id, i.trait_type_id), (TraitEntry::new(&i), i.site));
+        let Some((replaced_entry, replaced_site)) = replaced else {
+            traits_of.entry(i.implementor.type_id).or_default().push(i.trait_type_id);
+            continue;
+        };
+        let index = *duplicate_indices.entry((i.implementor.type_id, i.trait_type_id)).or_insert_with(|| {
+            duplicates.push(DuplicateRegistration {
+                implementor: i.implementor,
+                trait_info: TraitInfo { trait_id: i.trait_type_id, trait_name: i.trait_name },
+                registrations: vec![(replaced_site, replaced_entry.v_table.is_some())],
+            });
+            duplicates.len() - 1
+        });
+        let duplicate = &mut duplicates[index];
+        duplicate.registrations.push((i.site, i.v_table.is_some()));
+        if strict && !duplicate.answers_agree() {
+            panic!(
+                "'{}' registered for '{}' with conflicting answers: {:?}",
+                i.implementor.type_name, i.trait_name, duplicate.registrations
+            );
+        }
     }
-    const trait AsDynImpl<Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>>>{
-        type ToReg : Sized;
-        fn vtable_getter() -> Option<VTable>;
+    let vtables = za_hash.into_iter().map(|(key, (entry, _))| (key, entry)).collect();
+    for trait_implementors in implementors.values_mut() {
+        trait_implementors.sort_by_key(|info| info.type_name);
     }
-    impl<Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>> + 'static, Type: 'static> const AsDynImpl<Trait> for AsDyn<Type> {
-        type ToReg = Type;
-        default fn vtable_getter() -> Option<VTable>{
-            None
+    let mut registry = Registry { vtables, traits_of, implementors, principals, trait_names_of: Map::default(), diagnostics: RegistryDiagnostics { duplicates }, build_time: None };
+    registry.trait_names_of = registry.traits_of.keys().map(|type_id| (*type_id, registry.trait_names(*type_id))).collect();
+    registry
+}
+
+fn sorted_names(names: impl IntoIterator<Item = &'static str>) -> Arc<[&'static str]> {
+    let mut names: Vec<&'static str> = names.into_iter().collect();
+    names.sort_unstable();
+    names.into()
+}
+
+    static VTABLE_REGISTRY: Lazy<Registry> = Lazy::new(||{
+        build_vtable_registry(submitted_entries(), cfg!(feature = "strict-registration"))
+    });
+
+/// The size of a registry, see [`Registry::stats`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegistryStats {
+    /// Registered (implementor, trait) combinations
+    pub entries: usize,
+    /// Registered implementors
+    pub implementors: usize,
+    /// How long building the registry from its entries took. `None` for registries filled with
+    /// [`Registry::insert`], and without the `std` feature, which has no clock
+    pub build_time: Option<Duration>,
+    /// Approximate heap memory of the registry's tables in bytes, not counting spare capacity
+    pub memory: usize,
+}
+
+/// Builds the global registry now, if it is not built yet, instead of during the first cast.
+///
+/// Call it at startup to keep the cost of walking every registration off the first cast. The
+/// statistics are those of the registrations made with the macros, runtime ones are not included.
+pub fn init() -> RegistryStats {
+    VTABLE_REGISTRY.stats()
+}
+
+/// Whether the global registry has been built, by [`init`] or by a cast
+pub fn is_initialized() -> bool {
+    VTABLE_REGISTRY.get().is_some()
+}
+
+/// Duplicate registrations found while building the global registry.
+///
+/// Enable the `strict-registration` feature to panic on the first one whose answers disagree
+/// instead. Cargo unifies features across the dependency graph, so if any crate in the build
+/// enables it, every crate's registrations are checked.
+pub fn diagnostics() -> &'static RegistryDiagnostics {
+    VTABLE_REGISTRY.diagnostics()
+}
+
+    // Registrations made with `register`, never removed or changed, so casts read them without locks
+    static RUNTIME_REGISTRY: RuntimeRegistry = RuntimeRegistry::new();
+    // Serializes registrations, so a combination is not registered twice between checking and
+    // inserting
+    static RUNTIME_REGISTRY_WRITER: WriterLock = WriterLock::new();
+
+/// A combination of the global registry, from the static registry or else the runtime one
+#[cfg(not(feature = "inline-cache"))]
+fn find_entry(obj_type_id: ImplementorTypeId, trait_id: TraitTypeId, carried: AutoTraits) -> Option<Found<'static>> {
+    find_registered(obj_type_id, trait_id, carried)
+}
+
+/// A combination of the global registry, from the cache, or else the static registry or the
+/// runtime one
+#[cfg(feature = "inline-cache")]
+fn find_entry(obj_type_id: ImplementorTypeId, trait_id: TraitTypeId, carried: AutoTraits) -> Option<Found<'static>> {
+    if let Some(cached) = inline_cache::get(obj_type_id, trait_id, carried) {
+        return Some(cached);
+    }
+    let found = find_registered(obj_type_id, trait_id, carried)?;
+    inline_cache::insert(found.0, trait_id);
+    Some(found)
+}
+
+fn find_registered(obj_type_id: ImplementorTypeId, trait_id: TraitTypeId, carried: AutoTraits) -> Option<Found<'static>> {
+    let entry = |trait_id| find_registered_entry(obj_type_id, trait_id);
+    if let Some(found) = entry(trait_id) {
+        return Some((found, found.v_table));
+    }
+    find_variant(entry, global_principals_of(trait_id), carried)
+}
+
+fn find_registered_entry(obj_type_id: ImplementorTypeId, trait_id: TraitTypeId) -> Option<&'static TraitEntry> {
+    VTABLE_REGISTRY.entry(obj_type_id, trait_id)
+        .or_else(|| RUNTIME_REGISTRY.entry(obj_type_id, trait_id))
+}
+
+/// The traits of the global registry `trait_id` is a `Send`/`Sync` variant of
+fn global_principals_of(trait_id: TraitTypeId) -> impl Iterator<Item = &'static (TraitTypeId, usize)> {
+    VTABLE_REGISTRY.principals_of(trait_id)
+        .chain(RUNTIME_REGISTRY.principals_of(trait_id))
+}
+
+/// Registers `Type` for the trait object type `Trait` at runtime, for types that can not be
+/// registered with `register_types!`, such as instantiations only known to generic code.
+/// Registering a combination that is already registered does nothing.
+///
+/// `Trait` with `Send` and `Sync` added is covered too, as far as `Type` implements them, once
+/// those trait object types are known: that is when `Trait` is registered with a macro, such as
+/// `register_types!` or [`vtable_entry!`](crate::vtable_entry) with [`register_entry`]. Generic
+/// code can not name `Trait + Send` on its own.
+///
+/// Registrations take a lock, and are never freed since casts may still be reading them: casts
+/// read them without locks.
+#[cfg(feature = "nightly")]
+#[track_caller]
+pub fn register<Type: 'static, Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>> + 'static>() {
+    if find_entry(TypeId::of::<Type>(), TypeId::of::<Trait>(), AutoTraits::SEND_SYNC).is_some() {
+        return;
+    }
+    register_entry(generic_entry::<Type, Trait>());
+}
+
+/// [`register`] for an entry made with [`vtable_entry!`](crate::vtable_entry), which also works
+/// on stable Rust. Like in [`Registry::from_entries`], the entry is trusted as it is: only the
+/// macros and the unsafe [`VTableMapInstance::new`] make them.
+pub fn register_entry(entry: VTableMapInstance) {
+    let type_id = entry.implementor.type_id;
+    let trait_id = entry.trait_type_id;
+    if find_entry(type_id, trait_id, AutoTraits::SEND_SYNC).is_some() {
+        return;
+    }
+    let _writer = RUNTIME_REGISTRY_WRITER.lock();
+    // Another thread may have registered it while we waited
+    if find_entry(type_id, trait_id, AutoTraits::SEND_SYNC).is_some() {
+        return;
+    }
+    let macro_trait_names = VTABLE_REGISTRY.registered_trait_names(type_id).map_or(&[][..], |names| &names[..]);
+    RUNTIME_REGISTRY.insert(&entry, macro_trait_names);
+}
+
+/// The entry of a runtime registration made from generic code, located at its caller. Generic
+/// code can not name the `Send`/`Sync` variants of `Trait`, but it can tell which of them `Type`
+/// implements, and auto traits have no methods: the vtables of the variants are that of `Trait`.
+#[cfg(feature = "nightly")]
+#[track_caller]
+fn generic_entry<Type: 'static, Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>> + 'static>() -> VTableMapInstance {
+    let caller = core::panic::Location::caller();
+    let v_table = generate_trait_vtable::<Type, Trait>();
+    let send = generate_trait_vtable::<Type, dyn Send>().is_some();
+    let sync = generate_trait_vtable::<Type, dyn Sync>().is_some();
+    // SAFETY: the vtable is `Type`'s for `Trait`, or none
+    let entry = unsafe {
+        VTableMapInstance::new(
+            ImplementorInfo::of::<Type>(),
+            TypeId::of::<Trait>(),
+            const_type_name::<Trait>(),
+            v_table,
+            RegistrationSite::new("", caller.file(), caller.line()),
+        )
+    };
+    VTableMapInstance {
+        variant_v_tables: Some([send, sync, send && sync].map(|implemented| v_table.filter(|_| implemented))),
+        ..entry
+    }
+}
+
+/// Gets the vtable from `registry`, or from the global registry if `None`, for a pointer whose
+/// trait object type has the auto traits `carried`. Only the metadata of `obj` is used, so it
+/// does not need to point to a live object
+#[cfg(feature = "nightly")]
+pub(crate) fn get_vtable<TCastTo: CastTarget + ?Sized>(obj: *const dyn Castable, carried: AutoTraits, registry: Option<&Registry>) -> Result<VTable, CastError>{
+    get_vtable_in::<TCastTo>(obj.type_id_of_ptr(), obj.type_name_of_ptr(), carried, registry)
+}
+
+/// Gets the vtable from `registry`, or from the global registry if `None`, for a pointer whose
+/// trait object type has the auto traits `carried`. Stable Rust can only read the concrete type
+/// through a live object
+#[cfg(not(feature = "nightly"))]
+pub(crate) fn get_vtable<TCastTo: CastTarget + ?Sized>(obj: &dyn Castable, carried: AutoTraits, registry: Option<&Registry>) -> Result<VTable, CastError>{
+    get_vtable_in::<TCastTo>(obj.type_id(), obj.type_name(), carried, registry)
+}
+
+fn get_vtable_in<TCastTo: CastTarget + ?Sized>(obj_type_id: TypeId, obj_type_name: &'static str, carried: AutoTraits, registry: Option<&Registry>) -> Result<VTable, CastError>{
+    let find = |carried| match registry {
+        Some(registry) => registry.find(obj_type_id, TypeId::of::<TCastTo>(), carried),
+        None => find_entry(obj_type_id, TypeId::of::<TCastTo>(), carried),
+    };
+    let mut found = find(carried);
+    if found.is_none() && carried != AutoTraits::SEND_SYNC {
+        // A variant the pointer does not carry the auto traits of is still registered
+        found = find(AutoTraits::SEND_SYNC);
+        if found.is_some_and(|(_, v_table)| v_table.is_some()) {
+            return Err(CastError::AutoTraitsNotCarried{trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj_type_name, type_id: obj_type_id });
         }
     }
-    impl<Type: Unsize<Trait> + 'static,Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>> + 'static> const AsDynImpl<Trait> for AsDyn<Type> {
-        fn vtable_getter()  -> Option<VTable>{
-            unsafe{  Some(transmute(metadata(null::<Type>() as *const Trait))) }
+    vtable_of_entry::<TCastTo>(found, obj_type_id, obj_type_name, registry)
+}
+
+/// The vtable of the combination found in `registry`, or in the global registry if `None`, which
+/// is also where the type is checked for other registrations when the combination is not
+/// registered
+fn vtable_of_entry<TCastTo: CastTarget + ?Sized>(found: Option<Found<'_>>, obj_type_id: TypeId, obj_type_name: &'static str, registry: Option<&Registry>) -> Result<VTable, CastError>{
+    match found.map(|(_, v_table)| v_table) {
+        None => {
+            let registered_traits = match registry {
+                Some(registry) => registry.registered_trait_names(obj_type_id),
+                // The runtime names include those of the macros, if the type has any
+                None => RUNTIME_REGISTRY.registered_trait_names(obj_type_id).or_else(|| VTABLE_REGISTRY.registered_trait_names(obj_type_id)),
+            };
+            let scoped = registry.is_some();
+            let Some(registered_traits) = registered_traits else {
+                return Err(CastError::TypeNotRegistered{trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj_type_name, type_id: obj_type_id, scoped });
+            };
+            Err(CastError::CombinationNotRegistered{trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj_type_name, type_id: obj_type_id, scoped, registered_traits: registered_traits.clone() })
+        }
+        Some(gotten) => {
+            match &gotten {
+                None => {
+                    Err(CastError::TraitNotImplemented {trait_name: type_name::<TCastTo>(), trait_id: TypeId::of::<TCastTo>(), type_name: obj_type_name, type_id: obj_type_id })
+                } Some(found) => {
+                    Ok(*found)
+                }
+            }
         }
     }
+}
+
+/// Turns a vtable lookup into a yes/no answer, keeping "not registered" as an error
+fn vtable_found(lookup: Result<VTable, CastError>) -> Result<bool, CastError> {
+    match lookup {
+        Ok(_) => Ok(true),
+        Err(CastError::TraitNotImplemented { .. }) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Checks whether the concrete type behind `obj` implements `TTo`, without producing a pointer.
+///
+/// Returns `Err(CastError::CombinationNotRegistered)` or `Err(CastError::TypeNotRegistered)` when
+/// the registry has no answer for this combination, so "not implemented" and "not registered" can
+/// be told apart.
+pub fn implements<TTo: CastTarget + ?Sized>(obj: &dyn Castable) -> Result<bool, CastError> {
+    vtable_found(get_vtable::<TTo>(obj, AutoTraits::SEND_SYNC, None))
+}
+
+/// Type-level version of [`implements`].
+pub fn type_implements<Type: 'static, TTo: CastTarget + ?Sized>() -> Result<bool, CastError> {
+    vtable_found(get_vtable_in::<TTo>(TypeId::of::<Type>(), type_name::<Type>(), AutoTraits::SEND_SYNC, None))
+}
+
+impl dyn Castable {
+    /// Method version of [`implements`].
+    pub fn implements<TTo: CastTarget + ?Sized>(&self) -> Result<bool, CastError> {
+        implements::<TTo>(self)
+    }
+}
 
-    <AsDyn<Type> as AsDynImpl<Trait>>::vtable_getter()
+/// A registered trait object type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TraitInfo {
+    pub trait_id: TraitTypeId,
+    pub trait_name: &'static str,
 }
-struct INVALID;
+
+/// Every trait a concrete type has been registered with, split by whether it implements it.
+/// Both lists are sorted by trait name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegisteredTraits {
+    pub implemented: Vec<TraitInfo>,
+    pub not_implemented: Vec<TraitInfo>,
+}
+
+fn collect_registered_traits<'a>(traits: impl IntoIterator<Item = (&'a TraitTypeId, &'a TraitEntry)>) -> RegisteredTraits {
+    let mut registered = RegisteredTraits::default();
+    for (trait_id, entry) in traits {
+        let info = TraitInfo { trait_id: *trait_id, trait_name: entry.trait_name };
+        match entry.v_table {
+            Some(_) => registered.implemented.push(info),
+            None => registered.not_implemented.push(info),
+        }
+    }
+    registered.implemented.sort_by_key(|info| info.trait_name);
+    registered.not_implemented.sort_by_key(|info| info.trait_name);
+    registered
+}
+
+/// Lists the registered traits of the concrete type identified by `type_id`.
+///
+/// Returns `None` if the type has not been registered at all.
+pub fn registered_traits_of_type_id(type_id: TypeId) -> Option<RegisteredTraits> {
+    let static_traits = VTABLE_REGISTRY.registered_entries(type_id);
+    let runtime_traits = RUNTIME_REGISTRY.registered_entries(type_id);
+    if static_traits.is_none() && runtime_traits.is_none() {
+        return None;
+    }
+    // A combination is only registered at runtime if it is not registered statically
+    Some(collect_registered_traits(static_traits.into_iter().flatten().chain(runtime_traits.into_iter().flatten())))
+}
+
+/// Lists the registered traits of the concrete type behind `obj`.
+pub fn registered_traits_of(obj: &dyn Castable) -> Option<RegisteredTraits> {
+    registered_traits_of_type_id(obj.type_id())
+}
+
+/// Lists every registered type that implements `TTo`, sorted by type name.
+pub fn implementors_of<TTo: CastTarget + ?Sized>() -> Vec<ImplementorInfo> {
+    let trait_id = TypeId::of::<TTo>();
+    let principals = global_principals_of(trait_id).map(|(principal, _)| *principal);
+    let candidates = core::iter::once(trait_id).chain(principals)
+        .flat_map(|trait_id| VTABLE_REGISTRY.implementors.get(&trait_id).into_iter().flatten().chain(RUNTIME_REGISTRY.implementors(trait_id)));
+    implementing(candidates, |type_id| find_registered(type_id, trait_id, AutoTraits::SEND_SYNC))
+}
+
 #[macro_export]
 macro_rules! register_types {
     // Entry: two comma-separated lists (trailing commas ok)
-    (implementors: [$($impl:ty),* $(,)?], traits: [$($tr:path),* $(,)?]) => {
+    // Implementors are either types, or generic patterns with the instantiations to register,
+    // e.g. `Wrapper<T> for T in (u8, String)` or `Buffer<N> for const N: usize in (16, 32)`.
+    // Several `for` clauses register every combination of their instantiations.
+    // Traits are either a trait path (`Child`, `Handler<Click>`, `Fn(&str) -> bool`), or a full
+    // trait object type (`dyn Iterator<Item = u32> + Send`), registered as written. Either way
+    // the registration also answers for the trait object type with `Send` and `Sync` added.
+    // A cast only reaches those variants with auto traits the pointer being cast has in its type:
+    // `Rc<dyn Base + Send>` casts to `Rc<dyn Child + Send>`, `Rc<dyn Base>` does not.
+    (implementors: [$($implementors:tt)*], traits: [$($traits:tt)*]) => {
         // Recur over implementors
-        $crate::register_types!(@impls [$($impl),*] @traits [$($tr),*]);
+        $crate::register_types!(@impls [$($implementors)*] @traits [$($traits)*]);
     };
 
+    // Done with implementors
+    (@impls [] @traits [$($traits:tt)*]) => {};
     // Consume one implementor, keep the full traits list intact
-    (@impls [$head:ty $(, $tail:ty)*] @traits [$($tr:path),*]) => {
-        $crate::register_types!(@for_one_impl $head; [$($tr),*]);
-        $crate::register_types!(@impls [$($tail),*] @traits [$($tr),*]);
+    (@impls [$head:ty $(, $($tail:tt)*)?] @traits [$($traits:tt)*]) => {
+        $crate::register_types!(@for_one_impl $head; [$($traits)*]);
+        $crate::register_types!(@impls [$($($tail)*)?] @traits [$($traits)*]);
+    };
+    // Not a plain type, so a generic pattern. A type fragment can not be followed by `for`,
+    // so the pattern's tokens are collected one by one
+    (@impls [$($pattern_and_rest:tt)*] @traits $traits:tt) => {
+        $crate::register_types!(@generic_pattern [] [$($pattern_and_rest)*] @traits $traits);
+    };
+
+    (@generic_pattern [$($pattern:tt)*] [for $($rest:tt)*] @traits $traits:tt) => {
+        $crate::register_types!(@generic_clauses [$($pattern)*] [] [] [for $($rest)*] @traits $traits);
+    };
+    (@generic_pattern [$($pattern:tt)*] [$next:tt $($rest:tt)*] @traits $traits:tt) => {
+        $crate::register_types!(@generic_pattern [$($pattern)* $next] [$($rest)*] @traits $traits);
     };
-    // Done with implementors
-    (@impls [] @traits [$($tr:path),*]) => {};
 
-    // For a single implementor, munch the traits list one by one
-    (@for_one_impl $impl:ty; [$first:path $(, $rest:path)*]) => {
+    // Collect the generic parameters and their instantiation lists
+    (@generic_clauses $pattern:tt [$($params:tt)*] [$($lists:tt)*] [for const $param:ident : $const_ty:ident in ($($inst:expr),* $(,)?) $($rest:tt)*] @traits $traits:tt) => {
+        $crate::register_types!(@generic_clauses $pattern [$($params)* const $param: $const_ty,] [$($lists)* [$({$inst})*]] [$($rest)*] @traits $traits);
+    };
+    (@generic_clauses $pattern:tt [$($params:tt)*] [$($lists:tt)*] [for $param:ident in ($($inst:ty),* $(,)?) $($rest:tt)*] @traits $traits:tt) => {
+        $crate::register_types!(@generic_clauses $pattern [$($params)* $param,] [$($lists)* [$($inst)*]] [$($rest)*] @traits $traits);
+    };
+    // Pattern done: an alias turns the parameters back into something that can be instantiated
+    (@generic_clauses [$($pattern:tt)*] [$($params:tt)*] [$($lists:tt)*] [$(, $($rest:tt)*)?] @traits $traits:tt) => {
+        const _: () = {
+            type GenericImplementor<$($params)*> = $($pattern)*;
+            $crate::register_types!(@product [] [$($lists)*] @traits $traits);
+        };
+        $crate::register_types!(@impls [$($($rest)*)?] @traits $traits);
+    };
+
+    // Every combination of the instantiation lists
+    (@product [$($done:tt)*] [] @traits $traits:tt) => {
+        $crate::register_types!(@for_one_impl GenericImplementor<$($done)*>; $traits);
+    };
+    (@product $done:tt [$first:tt $($lists:tt)*] @traits $traits:tt) => {
+        $crate::register_types!(@product_each $done $first [$($lists)*] @traits $traits);
+    };
+    (@product_each $done:tt [$($inst:tt)*] $lists:tt @traits $traits:tt) => {
+        $($crate::register_types!(@product_step $done $inst $lists @traits $traits);)*
+    };
+    (@product_step [$($done:tt)*] $inst:tt $lists:tt @traits $traits:tt) => {
+        $crate::register_types!(@product [$($done)* $inst,] $lists @traits $traits);
+    };
+
+    // For a single implementor, munch the traits list one by one.
+    // `dyn` has to be matched before `path`, a path fragment fails hard on it
+    (@for_one_impl $impl:ty; [dyn $($rest:tt)*]) => {
+        $crate::register_types!(@for_one_dyn $impl; [dyn $($rest)*]);
+    };
+    (@for_one_impl $impl:ty; [$first:path $(, $($rest:tt)*)?]) => {
         $crate::register_types!(@emit $impl, $first);
-        $crate::register_types!(@for_one_impl $impl; [$($rest),*]);
+        $crate::register_types!(@for_one_impl $impl; [$($($rest)*)?]);
     };
     // Done with traits for this implementor
     (@for_one_impl $impl:ty; []) => {};
 
-    // The actual submission
+    // The bounds of a trait object type run until a comma outside of any `<...>`, the open
+    // brackets are counted in `$depth`
+    (@for_one_dyn $impl:ty; [dyn $($rest:tt)*]) => {
+        $crate::register_types!(@dyn_bounds $impl; [] [] [$($rest)*]);
+    };
+    (@dyn_bounds $impl:ty; [$($bounds:tt)*] [] [$(, $($rest:tt)*)?]) => {
+        $crate::register_types!(@submit $impl, [$($bounds)*]);
+        $crate::register_types!(@for_one_impl $impl; [$($($rest)*)?]);
+    };
+    (@dyn_bounds $impl:ty; [$($bounds:tt)*] [$($depth:tt)*] [< $($rest:tt)*]) => {
+        $crate::register_types!(@dyn_bounds $impl; [$($bounds)* <] [< $($depth)*] [$($rest)*]);
+    };
+    (@dyn_bounds $impl:ty; [$($bounds:tt)*] [$($depth:tt)*] [<< $($rest:tt)*]) => {
+        $crate::register_types!(@dyn_bounds $impl; [$($bounds)* <<] [< < $($depth)*] [$($rest)*]);
+    };
+    (@dyn_bounds $impl:ty; [$($bounds:tt)*] [< $($depth:tt)*] [> $($rest:tt)*]) => {
+        $crate::register_types!(@dyn_bounds $impl; [$($bounds)* >] [$($depth)*] [$($rest)*]);
+    };
+    (@dyn_bounds $impl:ty; [$($bounds:tt)*] [< < $($depth:tt)*] [>> $($rest:tt)*]) => {
+        $crate::register_types!(@dyn_bounds $impl; [$($bounds)* >>] [$($depth)*] [$($rest)*]);
+    };
+    (@dyn_bounds $impl:ty; [$($bounds:tt)*] $depth:tt [$next:tt $($rest:tt)*]) => {
+        $crate::register_types!(@dyn_bounds $impl; [$($bounds)* $next] $depth [$($rest)*]);
+    };
+
+    // A single entry, which also holds the `Send`/`Sync` variants of `dyn $tr`, see
+    // `vtable_entry!`
     (@emit $impl:ty, $tr:path) => {
-        inventory::submit! {
+        $crate::register_types!(@submit $impl, [$tr]);
+    };
+
+    // The actual submission
+    (@submit $impl:ty, [$($bounds:tt)*]) => {
+        $crate::__submit_entry!($impl, dyn $($bounds)*);
+    };
+}
+
+/// Builds the [`VTableMapInstance`] registering `$impl` for the trait object type `dyn ...`, for
+/// [`Registry::insert`] and [`register_entry`]:
+///
+/// ```ignore
+/// registry.insert(vtable_entry!(Foo, dyn Child + Send));
+/// ```
+///
+/// The entry also answers for the trait object type with `Send` and `Sync` added, as far as
+/// `$impl` implements them.
+#[cfg(feature = "nightly")]
+#[macro_export]
+macro_rules! vtable_entry {
+    ($impl:ty, dyn $($bounds:tt)+) => {{
+        let implementor = $crate::trait_registry::ImplementorInfo::of::<$impl>();
+        let trait_type_id = ::core::any::TypeId::of::<dyn $($bounds)+>();
+        let trait_name = $crate::trait_registry::const_type_name::<dyn $($bounds)+>();
+        let v_table = $crate::trait_registry::generate_trait_vtable::<$impl, dyn $($bounds)+>();
+        let variant_ids = [
+            ::core::any::TypeId::of::<dyn $($bounds)+ + ::core::marker::Send>(),
+            ::core::any::TypeId::of::<dyn $($bounds)+ + ::core::marker::Sync>(),
+            ::core::any::TypeId::of::<dyn $($bounds)+ + ::core::marker::Send + ::core::marker::Sync>(),
+        ];
+        let variant_v_tables = [
+            $crate::trait_registry::generate_trait_vtable::<$impl, dyn $($bounds)+ + ::core::marker::Send>(),
+            $crate::trait_registry::generate_trait_vtable::<$impl, dyn $($bounds)+ + ::core::marker::Sync>(),
+            $crate::trait_registry::generate_trait_vtable::<$impl, dyn $($bounds)+ + ::core::marker::Send + ::core::marker::Sync>(),
+        ];
+        let site = $crate::trait_registry::RegistrationSite::new(::core::module_path!(), ::core::file!(), ::core::line!());
+        // SAFETY: every vtable is the implementor's for the trait object type next to it, or none
+        unsafe {
+            $crate::trait_registry::VTableMapInstance::new(implementor, trait_type_id, trait_name, v_table, site)
+                .with_auto_variants(variant_ids, variant_v_tables)
+        }
+    }};
+}
 
-            $crate::trait_registry::VTableMapInstance::new(::core::any::TypeId::of::<$impl>(), ::core::any::TypeId::of::<dyn $tr>(),$crate::trait_registry::generate_trait_vtable::<$impl,dyn $tr>())
+/// Builds the [`VTableMapInstance`] registering `$impl` for the trait object type `dyn ...`, for
+/// [`Registry::insert`] and [`register_entry`]:
+///
+/// ```ignore
+/// registry.insert(vtable_entry!(Foo, dyn Child + Send));
+/// ```
+///
+/// The entry also answers for the trait object type with `Send` and `Sync` added, as far as
+/// `$impl` implements them.
+#[cfg(not(feature = "nightly"))]
+#[macro_export]
+macro_rules! vtable_entry {
+    ($impl:ty, dyn $($bounds:tt)+) => {{
+        #[allow(unused_imports)]
+        use $crate::trait_registry::__private::{HasDefault as _, NoDefault as _};
+        let implementor = $crate::trait_registry::ImplementorInfo::of::<$impl>()
+            .with_constructor((&$crate::trait_registry::__private::DefaultProbe::<$impl>::new()).constructor());
+        let trait_type_id = ::core::any::TypeId::of::<dyn $($bounds)+>();
+        let trait_name = $crate::trait_registry::const_type_name::<dyn $($bounds)+>();
+        let v_table = $crate::__vtable_probe!($impl, dyn $($bounds)+);
+        let variant_ids = [
+            ::core::any::TypeId::of::<dyn $($bounds)+ + ::core::marker::Send>(),
+            ::core::any::TypeId::of::<dyn $($bounds)+ + ::core::marker::Sync>(),
+            ::core::any::TypeId::of::<dyn $($bounds)+ + ::core::marker::Send + ::core::marker::Sync>(),
+        ];
+        let variant_v_tables = [
+            $crate::__vtable_probe!($impl, dyn $($bounds)+ + ::core::marker::Send),
+            $crate::__vtable_probe!($impl, dyn $($bounds)+ + ::core::marker::Sync),
+            $crate::__vtable_probe!($impl, dyn $($bounds)+ + ::core::marker::Send + ::core::marker::Sync),
+        ];
+        let site = $crate::trait_registry::RegistrationSite::new(::core::module_path!(), ::core::file!(), ::core::line!());
+        // SAFETY: every vtable is the implementor's for the trait object type next to it, or none
+        unsafe {
+            $crate::trait_registry::VTableMapInstance::new(implementor, trait_type_id, trait_name, v_table, site)
+                .with_auto_variants(variant_ids, variant_v_tables)
+        }
+    }};
+}
+
+// The vtable of `$impl` for `dyn ...`, or `None`. Autoref specialization: the method of the impl
+// on the probe itself only applies when `$impl` implements the bounds, otherwise the one on
+// `&probe` is picked.
+#[cfg(not(feature = "nightly"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __vtable_probe {
+    ($impl:ty, dyn $($bounds:tt)+) => {{
+        struct __IzaVTableProbe<__IzaType>(::core::marker::PhantomData<__IzaType>);
+        trait __IzaImplemented {
+            fn v_table(&self) -> ::core::option::Option<$crate::trait_registry::VTable>;
         }
-        // Optional: enforce `$impl: $tr` at compile time
-        // const _: fn() = || { fn _assert<T: $tr>() {} _assert::<$impl>(); };
+        impl<__IzaType: $($bounds)+ + 'static> __IzaImplemented for __IzaVTableProbe<__IzaType> {
+            #[allow(unused_parens)]
+            fn v_table(&self) -> ::core::option::Option<$crate::trait_registry::VTable> {
+                let caster: fn(*const ()) -> *const (dyn $($bounds)+) = |data| data as *const __IzaType as *const (dyn $($bounds)+);
+                // SAFETY: the caster reads the data pointer as the implementor, `__IzaType`
+                ::core::option::Option::Some(unsafe { $crate::trait_registry::VTable::from_caster(caster) })
+            }
+        }
+        trait __IzaNotImplemented {
+            fn v_table(&self) -> ::core::option::Option<$crate::trait_registry::VTable> {
+                ::core::option::Option::None
+            }
+        }
+        impl<__IzaType> __IzaNotImplemented for &__IzaVTableProbe<__IzaType> {}
+
+        (&__IzaVTableProbe::<$impl>(::core::marker::PhantomData)).v_table()
+    }};
+}
+
+// The value submitted for an entry, see `SubmittedEntry`
+#[cfg(feature = "nightly")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __submitted_entry {
+    ($impl:ty, dyn $($bounds:tt)+) => {
+        $crate::vtable_entry!($impl, dyn $($bounds)+)
+    };
+}
+
+#[cfg(not(feature = "nightly"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __submitted_entry {
+    ($impl:ty, dyn $($bounds:tt)+) => {
+        $crate::trait_registry::DeferredEntry::new({
+            fn entry() -> $crate::trait_registry::VTableMapInstance {
+                $crate::vtable_entry!($impl, dyn $($bounds)+)
+            }
+            entry
+        })
     };
-}
\ No newline at end of file
+}
+
+#[cfg(not(feature = "linkme"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __submit_entry {
+    ($impl:ty, dyn $($bounds:tt)+) => {
+        inventory::submit! {
+            $crate::__submitted_entry!($impl, dyn $($bounds)+)
+        }
+    };
+}
+
+#[cfg(feature = "linkme")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __submit_entry {
+    ($impl:ty, dyn $($bounds:tt)+) => {
+        const _: () = {
+            #[$crate::__linkme::distributed_slice($crate::trait_registry::SUBMITTED_ENTRIES)]
+            #[linkme(crate = $crate::__linkme)]
+            static ENTRY: $crate::trait_registry::SubmittedEntry = $crate::__submitted_entry!($impl, dyn $($bounds)+);
+        };
+    };
+}
@@ -0,0 +1,114 @@
+// The cache in front of the global registry, for the `inline-cache` feature. There are no generic
+// statics, so there is no cache per cast instantiation. Instead each trait object type looked up
+// gets slots of its own, picked by the type: a cast site that keeps seeing the same concrete type
+// keeps hitting its slot, and lookups of other traits never evict it. Types of the same trait
+// that land on the same slot evict each other, and a miss is two loads on top of the registry
+// lookup, so a working set larger than the slots of a trait (the `working_set` benchmark) is no
+// slower than without it. Once `TRAITS` traits have slots, the others are not cached.
+//
+// Entries of the global registry are never removed or changed: runtime registrations only add
+// combinations, in nodes that are leaked. So a cached entry stays right forever, and a slot
+// only has to publish a pointer to it, without locks or sequence counters. Combinations that are
+// not registered are not cached, they may be registered later.
+use alloc::boxed::Box;
+use core::hash::{Hash, Hasher};
+use core::ptr::{self, null_mut};
+use core::sync::atomic::{AtomicPtr, Ordering};
+use crate::sync::TypeIdHasher;
+use super::{AutoTraits, Found, ImplementorTypeId, TraitEntry, TraitTypeId, AUTO_VARIANTS};
+
+const TRAITS: usize = 64;
+const SLOTS: usize = 64;
+
+/// The slots of one trait object type, by implementor
+struct TraitSlots {
+    trait_id: TraitTypeId,
+    slots: [AtomicPtr<TraitEntry>; SLOTS],
+}
+
+// Open addressing by trait. Rows are claimed once and leaked, like the entries they point to
+static TRAIT_SLOTS: [AtomicPtr<TraitSlots>; TRAITS] = [const { AtomicPtr::new(null_mut()) }; TRAITS];
+
+fn hash(type_id: TraitTypeId) -> usize {
+    let mut hasher = TypeIdHasher::default();
+    type_id.hash(&mut hasher);
+    hasher.finish() as usize
+}
+
+/// The rows `trait_id` may be in, in probing order
+fn rows(trait_id: TraitTypeId) -> impl Iterator<Item = &'static AtomicPtr<TraitSlots>> {
+    let start = hash(trait_id);
+    (0..TRAITS).map(move |probe| &TRAIT_SLOTS[(start + probe) % TRAITS])
+}
+
+fn trait_slots(trait_id: TraitTypeId) -> Option<&'static TraitSlots> {
+    for row in rows(trait_id) {
+        // SAFETY: rows only hold leaked slots, which are never freed
+        let slots = unsafe { row.load(Ordering::Acquire).as_ref()? };
+        if slots.trait_id == trait_id {
+            return Some(slots);
+        }
+    }
+    None
+}
+
+/// The slots of `trait_id`, claiming a row for them if it has none yet
+fn claim_trait_slots(trait_id: TraitTypeId) -> Option<&'static TraitSlots> {
+    if let Some(slots) = trait_slots(trait_id) {
+        return Some(slots);
+    }
+    let new = Box::into_raw(Box::new(TraitSlots { trait_id, slots: [const { AtomicPtr::new(null_mut()) }; SLOTS] }));
+    let mut claimed = None;
+    for row in rows(trait_id) {
+        match row.compare_exchange(null_mut(), new, Ordering::AcqRel, Ordering::Acquire) {
+            // SAFETY: published, so leaked from now on
+            Ok(_) => return Some(unsafe { &*new }),
+            // Another thread claimed the row for `trait_id` first
+            // SAFETY: rows only hold leaked slots, which are never freed
+            Err(current) if unsafe { (*current).trait_id } == trait_id => {
+                claimed = Some(current);
+                break;
+            }
+            Err(_) => {}
+        }
+    }
+    // SAFETY: never published
+    drop(unsafe { Box::from_raw(new) });
+    // SAFETY: rows only hold leaked slots, which are never freed
+    claimed.map(|slots| unsafe { &*slots })
+}
+
+impl TraitSlots {
+    fn slot(&self, obj_type_id: ImplementorTypeId) -> &AtomicPtr<TraitEntry> {
+        &self.slots[hash(obj_type_id) % SLOTS]
+    }
+}
+
+/// The vtable `entry` has for `trait_id`, if it is the entry's trait or one of its variants that
+/// `carried` covers
+fn v_table_for(entry: &'static TraitEntry, trait_id: TraitTypeId, carried: AutoTraits) -> Option<Found<'static>> {
+    if entry.key.1 == trait_id {
+        return Some((entry, entry.v_table));
+    }
+    let variant = (0..AUTO_VARIANTS).find(|&variant| entry.variant_ids.is_some_and(|ids| ids[variant] == trait_id) && carried.cover(variant))?;
+    Some((entry, entry.variant_v_tables?[variant]))
+}
+
+pub(super) fn get(obj_type_id: ImplementorTypeId, trait_id: TraitTypeId, carried: AutoTraits) -> Option<Found<'static>> {
+    // SAFETY: slots only hold entries of the global registries, which are never freed or mutated
+    let cached = unsafe { trait_slots(trait_id)?.slot(obj_type_id).load(Ordering::Acquire).as_ref()? };
+    if cached.key.0 != obj_type_id {
+        return None;
+    }
+    v_table_for(cached, trait_id, carried)
+}
+
+/// Caches `entry` for lookups of `trait_id`, unless the entry can not tell it is for them
+pub(super) fn insert(entry: &'static TraitEntry, trait_id: TraitTypeId) {
+    if v_table_for(entry, trait_id, AutoTraits::SEND_SYNC).is_none() {
+        return;
+    }
+    if let Some(slots) = claim_trait_slots(trait_id) {
+        slots.slot(entry.key.0).store(ptr::from_ref(entry).cast_mut(), Ordering::Release);
+    }
+}
@@ -0,0 +1,216 @@
+// The registrations made at runtime with `register` and `register_entry`. Casts read them without
+// locks, so nothing is ever moved, changed or freed once published: entries live in leaked nodes,
+// and the hash tables indexing them only hold pointers to the nodes. A table that fills up is
+// replaced by one twice as large and leaked, since casts may still be probing it, so the leaked
+// tables add up to less than the one in use and each registration only costs its own nodes.
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, BuildHasherDefault, Hash};
+use core::marker::PhantomData;
+use core::ptr::{self, null_mut};
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use crate::sync::{TypeIdHasher, WriterLock};
+use super::{sorted_names, variant_rows, ImplementorInfo, ImplementorTypeId, TraitEntry, TraitTypeId, VTableMapInstance};
+
+/// The runtime counterpart of [`Registry`](super::Registry), with the same tables
+pub(super) struct RuntimeRegistry {
+    vtables: AppendMap<(ImplementorTypeId, TraitTypeId), TraitEntry>,
+    traits_of: AppendMap<ImplementorTypeId, AppendList<TraitTypeId>>,
+    implementors: AppendMap<TraitTypeId, AppendList<ImplementorInfo>>,
+    principals: AppendMap<TraitTypeId, AppendList<(TraitTypeId, usize)>>,
+    // Every list of names the type had, the current one first
+    trait_names_of: AppendMap<ImplementorTypeId, AppendList<Arc<[&'static str]>>>,
+}
+
+impl RuntimeRegistry {
+    pub(super) const fn new() -> Self {
+        Self { vtables: AppendMap::new(), traits_of: AppendMap::new(), implementors: AppendMap::new(), principals: AppendMap::new(), trait_names_of: AppendMap::new() }
+    }
+
+    /// Adds `entry`, unless its combination is already registered. Concurrent inserts are
+    /// memory safe, but callers serialize them so the same variant row is not added twice.
+    /// `macro_trait_names` are the traits the macros registered the type for, to list with the
+    /// ones registered here
+    pub(super) fn insert(&self, entry: &VTableMapInstance, macro_trait_names: &[&'static str]) {
+        let type_id = entry.implementor.type_id;
+        if self.entry(type_id, entry.trait_type_id).is_some() {
+            return;
+        }
+        // The entry goes first, so the rows and lists never lead to a missing one
+        self.vtables.get_or_insert_with((type_id, entry.trait_type_id), || TraitEntry::new(entry));
+        for (variant_id, row) in variant_rows(entry) {
+            let rows = self.principals.get_or_insert_with(variant_id, AppendList::new);
+            if !rows.iter().any(|registered| *registered == row) {
+                rows.push(row);
+            }
+        }
+        let traits = self.traits_of.get_or_insert_with(type_id, AppendList::new);
+        traits.push(entry.trait_type_id);
+        let runtime_names = traits.iter().filter_map(|trait_id| Some(self.entry(type_id, *trait_id)?.trait_name));
+        let names = sorted_names(macro_trait_names.iter().copied().chain(runtime_names));
+        self.trait_names_of.get_or_insert_with(type_id, AppendList::new).push(names);
+        if entry.v_table.is_some() {
+            self.implementors.get_or_insert_with(entry.trait_type_id, AppendList::new).push(entry.implementor);
+        }
+    }
+
+    pub(super) fn entry(&self, obj_type_id: ImplementorTypeId, trait_id: TraitTypeId) -> Option<&TraitEntry> {
+        self.vtables.get(&(obj_type_id, trait_id))
+    }
+
+    /// The registered traits `trait_id` is a `Send`/`Sync` variant of
+    pub(super) fn principals_of(&self, trait_id: TraitTypeId) -> impl Iterator<Item = &(TraitTypeId, usize)> {
+        self.principals.get(&trait_id).into_iter().flat_map(AppendList::iter)
+    }
+
+    /// The registered types that implement `trait_id`, in no particular order
+    pub(super) fn implementors(&self, trait_id: TraitTypeId) -> impl Iterator<Item = &ImplementorInfo> {
+        self.implementors.get(&trait_id).into_iter().flat_map(AppendList::iter)
+    }
+
+    /// The names of the traits the type is registered for, sorted, or `None` if it is not
+    /// registered at runtime
+    pub(super) fn registered_trait_names(&self, type_id: ImplementorTypeId) -> Option<&Arc<[&'static str]>> {
+        self.trait_names_of.get(&type_id)?.iter().next()
+    }
+
+    /// The registered combinations of the type, or `None` if it is not registered at all
+    pub(super) fn registered_entries(&self, type_id: ImplementorTypeId) -> Option<impl Iterator<Item = (&TraitTypeId, &TraitEntry)>> {
+        let traits = self.traits_of.get(&type_id)?;
+        Some(traits.iter().filter_map(move |trait_id| Some((trait_id, self.entry(type_id, *trait_id)?))))
+    }
+}
+
+/// A list that is only ever pushed to, and can be read while it is
+struct AppendList<T: 'static> {
+    head: AtomicPtr<ListNode<T>>,
+    // The list hands out `&T` to every thread, and takes values from any of them
+    _values: PhantomData<T>,
+}
+
+struct ListNode<T: 'static> {
+    value: T,
+    next: Option<&'static ListNode<T>>,
+}
+
+impl<T> AppendList<T> {
+    const fn new() -> Self {
+        Self { head: AtomicPtr::new(null_mut()), _values: PhantomData }
+    }
+
+    /// Pushes `value` to the front, and returns where it is kept
+    fn push(&self, value: T) -> &T {
+        let node = Box::leak(Box::new(ListNode { value, next: None }));
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            // SAFETY: the head is null or a leaked node, which is never freed or mutated
+            node.next = unsafe { head.as_ref() };
+            match self.head.compare_exchange_weak(head, ptr::from_mut(node), Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return &node.value,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// The values, last pushed first
+    fn iter(&self) -> impl Iterator<Item = &T> {
+        // SAFETY: the head is null or a leaked node, which is never freed or mutated
+        let mut next = unsafe { self.head.load(Ordering::Acquire).as_ref() };
+        core::iter::from_fn(move || {
+            let node = next?;
+            next = node.next;
+            Some(&node.value)
+        })
+    }
+}
+
+/// A hash map that is only ever inserted into, and can be read while it is
+struct AppendMap<K: 'static, V: 'static> {
+    entries: AppendList<(K, V)>,
+    // Open addressing over the entries, at most half full. Null until the first insert
+    table: AtomicPtr<Table<K, V>>,
+    len: AtomicUsize,
+    writer: WriterLock,
+}
+
+struct Table<K: 'static, V: 'static> {
+    slots: Box<[AtomicPtr<(K, V)>]>,
+}
+
+impl<K: Copy + Eq + Hash, V> AppendMap<K, V> {
+    const fn new() -> Self {
+        Self { entries: AppendList::new(), table: AtomicPtr::new(null_mut()), len: AtomicUsize::new(0), writer: WriterLock::new() }
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.table()?.get(key).map(|(_, value)| value)
+    }
+
+    /// The value of `key`, inserted as `value()` if there is none yet
+    fn get_or_insert_with(&self, key: K, value: impl FnOnce() -> V) -> &V {
+        if let Some(found) = self.get(&key) {
+            return found;
+        }
+        let _writer = self.writer.lock();
+        // Another thread may have inserted it while we waited
+        if let Some(found) = self.get(&key) {
+            return found;
+        }
+        let len = self.len.load(Ordering::Relaxed) + 1;
+        let table = match self.table() {
+            Some(table) if len * 2 <= table.slots.len() => table,
+            current => {
+                let grown = Table::with_capacity(current.map_or(8, |table| table.slots.len() * 2));
+                for entry in self.entries.iter() {
+                    grown.put(entry);
+                }
+                // The previous table is leaked, casts may still be probing it
+                let grown = Box::leak(Box::new(grown));
+                self.table.store(ptr::from_mut(grown), Ordering::Release);
+                grown
+            }
+        };
+        let entry = self.entries.push((key, value()));
+        table.put(entry);
+        self.len.store(len, Ordering::Relaxed);
+        &entry.1
+    }
+
+    fn table(&self) -> Option<&Table<K, V>> {
+        // SAFETY: the table is null or a leaked box, which is never freed
+        unsafe { self.table.load(Ordering::Acquire).as_ref() }
+    }
+}
+
+impl<K: Copy + Eq + Hash, V> Table<K, V> {
+    fn with_capacity(capacity: usize) -> Self {
+        Self { slots: (0..capacity).map(|_| AtomicPtr::new(null_mut())).collect::<Vec<_>>().into_boxed_slice() }
+    }
+
+    /// The slots to probe for `key`, in order. The capacity is a power of two
+    fn probe(&self, key: &K) -> impl Iterator<Item = &AtomicPtr<(K, V)>> {
+        let mask = self.slots.len() - 1;
+        let start = BuildHasherDefault::<TypeIdHasher>::default().hash_one(key) as usize;
+        (0..self.slots.len()).map(move |offset| &self.slots[start.wrapping_add(offset) & mask])
+    }
+
+    fn get(&self, key: &K) -> Option<&(K, V)> {
+        for slot in self.probe(key) {
+            // SAFETY: slots are null or point to the entries of a list node, which is never freed
+            // or mutated
+            let entry = unsafe { slot.load(Ordering::Acquire).as_ref()? };
+            if entry.0 == *key {
+                return Some(entry);
+            }
+        }
+        None
+    }
+
+    /// Indexes `entry`, which must be kept in a list node. Only called by the map's writer
+    fn put(&self, entry: &(K, V)) {
+        let slot = self.probe(&entry.0).find(|slot| slot.load(Ordering::Relaxed).is_null())
+            .expect("the table is at most half full");
+        slot.store(ptr::from_ref(entry).cast_mut(), Ordering::Release);
+    }
+}
@@ -0,0 +1,84 @@
+// The vtable and constructor lookups that need specialization, only compiled on nightly. Kept out
+// of line, the `const trait` syntax is rejected on stable even inside a disabled item.
+use alloc::boxed::Box;
+use core::marker::{PhantomData, Unsize};
+use core::mem::transmute;
+use core::ptr::{metadata, null, DynMetadata, Pointee};
+use super::{Castable, VTable};
+
+/// Gets the vtable of `Type` for the trait object type `Trait`, or `None` if `Type` does not
+/// implement it. `Trait` may be any trait object type, including generic arguments, associated
+/// type bindings and auto traits, e.g. `dyn Iterator<Item = u32> + Send`.
+pub const fn generate_trait_vtable<Type: 'static,Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>> + 'static>() -> Option<VTable> {
+    struct AsDyn<Type: 'static> {
+        kk: PhantomData<fn() -> Type>,
+    }
+    const trait AsDynImpl<Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>>>{
+        type ToReg : Sized;
+        fn vtable_getter() -> Option<VTable>;
+    }
+    impl<Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>> + 'static, Type: 'static> const AsDynImpl<Trait> for AsDyn<Type> {
+        type ToReg = Type;
+        default fn vtable_getter() -> Option<VTable>{
+            None
+        }
+    }
+    impl<Type: Unsize<Trait> + 'static,Trait: ?Sized + Pointee<Metadata=DynMetadata<Trait>> + 'static> const AsDynImpl<Trait> for AsDyn<Type> {
+        fn vtable_getter()  -> Option<VTable>{
+            unsafe{  Some(transmute::<DynMetadata<Trait>, VTable>(metadata(null::<Type>() as *const Trait))) }
+        }
+    }
+
+    <AsDyn<Type> as AsDynImpl<Trait>>::vtable_getter()
+}
+pub const fn generate_default_constructor<Type: 'static>() -> Option<fn() -> Box<dyn Castable>> {
+    struct WithDefault<Type: 'static> {
+        kk: PhantomData<fn() -> Type>,
+    }
+    const trait WithDefaultImpl {
+        fn constructor_getter() -> Option<fn() -> Box<dyn Castable>>;
+    }
+    impl<Type: 'static> const WithDefaultImpl for WithDefault<Type> {
+        default fn constructor_getter() -> Option<fn() -> Box<dyn Castable>> {
+            None
+        }
+    }
+    impl<Type: Default + 'static> const WithDefaultImpl for WithDefault<Type> {
+        fn constructor_getter() -> Option<fn() -> Box<dyn Castable>> {
+            fn construct<Type: Default + 'static>() -> Box<dyn Castable> {
+                Box::new(Type::default())
+            }
+            Some(construct::<Type>)
+        }
+    }
+
+    <WithDefault<Type> as WithDefaultImpl>::constructor_getter()
+}
+
+/// Whether `T` is `Send`, for trait object types too: `dyn Trait + Send` is, `dyn Trait` is not
+pub(crate) const fn is_send<T: ?Sized>() -> bool {
+    trait IsSend {
+        const SEND: bool;
+    }
+    impl<T: ?Sized> IsSend for T {
+        default const SEND: bool = false;
+    }
+    impl<T: ?Sized + Send> IsSend for T {
+        const SEND: bool = true;
+    }
+    <T as IsSend>::SEND
+}
+
+/// Whether `T` is `Sync`, see [`is_send`]
+pub(crate) const fn is_sync<T: ?Sized>() -> bool {
+    trait IsSync {
+        const SYNC: bool;
+    }
+    impl<T: ?Sized> IsSync for T {
+        default const SYNC: bool = false;
+    }
+    impl<T: ?Sized + Sync> IsSync for T {
+        const SYNC: bool = true;
+    }
+    <T as IsSync>::SYNC
+}